@@ -0,0 +1,6 @@
+UPDATE feedbacks SET created_at = NOW() WHERE created_at IS NULL;
+UPDATE feedbacks SET updated_at = created_at WHERE updated_at IS NULL;
+
+ALTER TABLE feedbacks
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN updated_at SET NOT NULL;
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS feedbacks_text_search_idx;
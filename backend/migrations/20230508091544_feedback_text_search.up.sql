@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS feedbacks_text_search_idx ON feedbacks USING GIN (to_tsvector('english', text));
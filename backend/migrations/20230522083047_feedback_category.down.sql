@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS feedbacks_category_idx;
+
+ALTER TABLE feedbacks DROP COLUMN IF EXISTS category;
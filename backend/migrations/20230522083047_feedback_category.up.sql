@@ -0,0 +1,3 @@
+ALTER TABLE feedbacks ADD COLUMN IF NOT EXISTS category VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS feedbacks_category_idx ON feedbacks (category);
@@ -0,0 +1,11 @@
+CREATE TABLE
+    IF NOT EXISTS feedback_replies (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        feedback_id UUID NOT NULL REFERENCES feedbacks (id) ON DELETE CASCADE,
+        text TEXT NOT NULL,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW()
+    );
+
+CREATE INDEX IF NOT EXISTS feedback_replies_feedback_id_idx ON feedback_replies (feedback_id, created_at);
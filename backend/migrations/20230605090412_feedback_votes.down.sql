@@ -0,0 +1,3 @@
+ALTER TABLE feedbacks
+    DROP COLUMN IF EXISTS upvotes,
+    DROP COLUMN IF EXISTS downvotes;
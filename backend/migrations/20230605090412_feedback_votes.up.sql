@@ -0,0 +1,3 @@
+ALTER TABLE feedbacks
+    ADD COLUMN IF NOT EXISTS upvotes INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS downvotes INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE feedbacks ADD COLUMN IF NOT EXISTS author_email VARCHAR(254);
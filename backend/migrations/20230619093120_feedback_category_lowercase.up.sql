@@ -0,0 +1 @@
+UPDATE feedbacks SET category = LOWER(category) WHERE category <> LOWER(category);
@@ -0,0 +1,14 @@
+CREATE TABLE
+    IF NOT EXISTS audit_log (
+        id UUID PRIMARY KEY NOT NULL DEFAULT (uuid_generate_v4()),
+        feedback_id UUID NOT NULL,
+        action VARCHAR(16) NOT NULL,
+        actor TEXT NOT NULL,
+        before JSONB,
+        after JSONB,
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE NOT NULL DEFAULT NOW()
+    );
+
+CREATE INDEX IF NOT EXISTS audit_log_feedback_id_idx ON audit_log (feedback_id, created_at);
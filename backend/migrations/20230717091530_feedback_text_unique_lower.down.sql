@@ -0,0 +1,9 @@
+DROP TRIGGER IF EXISTS feedbacks_unique_text ON feedbacks;
+
+DROP FUNCTION IF EXISTS feedbacks_unique_text();
+
+DROP INDEX IF EXISTS feedbacks_text_lower_idx;
+
+-- UNIQUE (text) is not restored: the trigger lets deleted feedback, case
+-- variants and deployments allowing duplicates share text, so rows written
+-- since the up migration would fail it.
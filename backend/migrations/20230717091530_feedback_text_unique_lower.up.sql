@@ -0,0 +1,36 @@
+ALTER TABLE feedbacks DROP CONSTRAINT IF EXISTS feedbacks_text_key;
+
+CREATE INDEX IF NOT EXISTS feedbacks_text_lower_idx ON feedbacks (LOWER(text)) WHERE deleted_at IS NULL;
+
+-- Live feedback must have unique text, ignoring case, unless the session sets
+-- feedback.unique_text to off. Checked by a trigger rather than a unique index
+-- so that deployments allowing duplicates share the same schema, and existing
+-- duplicates do not fail the migration.
+CREATE OR REPLACE FUNCTION feedbacks_unique_text() RETURNS trigger AS $$
+DECLARE
+    existing UUID;
+BEGIN
+    IF NEW.deleted_at IS NOT NULL
+        OR COALESCE(current_setting('feedback.unique_text', true), 'on') = 'off' THEN
+        RETURN NEW;
+    END IF;
+
+    -- Writers of the same text wait for each other, so both cannot pass.
+    PERFORM pg_advisory_xact_lock(hashtext(LOWER(NEW.text)));
+
+    SELECT id INTO existing FROM feedbacks
+    WHERE LOWER(text) = LOWER(NEW.text) AND deleted_at IS NULL AND id <> NEW.id
+    LIMIT 1;
+
+    IF existing IS NOT NULL THEN
+        RAISE EXCEPTION 'feedback % already has this text', existing
+            USING ERRCODE = 'unique_violation';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER feedbacks_unique_text
+    BEFORE INSERT OR UPDATE OF text, deleted_at ON feedbacks
+    FOR EACH ROW EXECUTE FUNCTION feedbacks_unique_text();
@@ -0,0 +1,49 @@
+-- The schema the Postgres migrations build up, in one step.
+--
+-- Ids are 16-byte blobs and are generated by the backend. Timestamps are UTC
+-- RFC 3339 text with milliseconds, written by the backend in that exact format
+-- so comparing them as text orders them in time. JSON is stored as text.
+--
+-- Live text is kept unique by the feedbacks_text_lower_idx index, which is
+-- created or dropped after migrations run, depending on ALLOW_DUPLICATE_TEXT.
+-- Unlike the Postgres trigger, it cannot be created while duplicates exist, so
+-- turning ALLOW_DUPLICATE_TEXT off again fails until they are resolved.
+CREATE TABLE
+    IF NOT EXISTS feedbacks (
+        id BLOB PRIMARY KEY NOT NULL,
+        rating INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        category VARCHAR(50),
+        author_email VARCHAR(254),
+        metadata TEXT,
+        upvotes INTEGER NOT NULL DEFAULT 0,
+        downvotes INTEGER NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        deleted_at TEXT
+    );
+
+CREATE INDEX IF NOT EXISTS feedbacks_category_idx ON feedbacks (category);
+
+CREATE TABLE
+    IF NOT EXISTS feedback_replies (
+        id BLOB PRIMARY KEY NOT NULL,
+        feedback_id BLOB NOT NULL REFERENCES feedbacks (id) ON DELETE CASCADE,
+        text TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+
+CREATE INDEX IF NOT EXISTS feedback_replies_feedback_id_idx ON feedback_replies (feedback_id, created_at);
+
+CREATE TABLE
+    IF NOT EXISTS audit_log (
+        id BLOB PRIMARY KEY NOT NULL,
+        feedback_id BLOB NOT NULL,
+        action VARCHAR(16) NOT NULL,
+        actor TEXT NOT NULL,
+        before TEXT,
+        after TEXT,
+        created_at TEXT NOT NULL
+    );
+
+CREATE INDEX IF NOT EXISTS audit_log_feedback_id_idx ON audit_log (feedback_id, created_at);
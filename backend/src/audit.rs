@@ -0,0 +1,108 @@
+use sqlx::{PgExecutor, Postgres, Transaction};
+
+use crate::{
+    error::ApiError,
+    model::{AuditEntryModel, FeedbackModel},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Anonymize,
+    Vote,
+    Touch,
+    /// A reply was added; the feedback itself is unchanged.
+    Reply,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+            AuditAction::Anonymize => "anonymize",
+            AuditAction::Vote => "vote",
+            AuditAction::Touch => "touch",
+            AuditAction::Reply => "reply",
+        }
+    }
+}
+
+/// Appends an entry to the audit trail. Takes the transaction the change
+/// itself ran in, so the entry is only kept if the change is.
+pub(crate) async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    action: AuditAction,
+    actor: &str,
+    before: Option<&FeedbackModel>,
+    after: Option<&FeedbackModel>,
+) -> Result<(), ApiError> {
+    let Some(feedback_id) = after.or(before).map(|feedback| feedback.id) else {
+        return Ok(());
+    };
+
+    let to_json = |feedback: &FeedbackModel| serde_json::to_value(feedback).unwrap_or_default();
+    let before = before.map(to_json);
+    let after = after.map(to_json);
+
+    sqlx::query!(
+        "INSERT INTO audit_log (feedback_id, action, actor, before, after)
+        VALUES ($1, $2, $3, $4, $5)",
+        feedback_id,
+        action.as_str(),
+        actor,
+        before,
+        after
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+/// What the text of erased feedback is replaced with. Carries the id since
+/// text is unique.
+pub(crate) fn redacted_text(feedback_id: uuid::Uuid) -> String {
+    format!("[redacted:{}]", feedback_id)
+}
+
+/// Scrubs the personal data of `feedback_id` from every snapshot of it in the
+/// trail, replacing the text with `placeholder`, so erasing it from the
+/// feedback does not leave a copy behind.
+pub(crate) async fn redact(
+    tx: &mut Transaction<'_, Postgres>,
+    feedback_id: uuid::Uuid,
+    placeholder: &str,
+) -> Result<(), ApiError> {
+    sqlx::query!(
+        r#"UPDATE audit_log
+        SET before = (before - 'authorEmail' - 'metadata') || jsonb_build_object('text', $2::text),
+            after = (after - 'authorEmail' - 'metadata') || jsonb_build_object('text', $2::text)
+        WHERE feedback_id = $1"#,
+        feedback_id,
+        placeholder
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Every audit entry for `feedback_id`, oldest first.
+pub async fn history(
+    executor: impl PgExecutor<'_>,
+    feedback_id: uuid::Uuid,
+) -> Result<Vec<AuditEntryModel>, sqlx::Error> {
+    sqlx::query_as!(
+        AuditEntryModel,
+        "SELECT * FROM audit_log WHERE feedback_id = $1 ORDER BY created_at, id",
+        feedback_id
+    )
+    .fetch_all(executor)
+    .await
+}
@@ -0,0 +1,58 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+use jsonwebtoken::{decode, errors::ErrorKind, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, AppState};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub sub: String,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatedUser {
+    pub user_id: String,
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(authenticate(req))
+    }
+}
+
+fn authenticate(req: &HttpRequest) -> Result<AuthenticatedUser, ApiError> {
+    let data = req
+        .app_data::<web::Data<AppState>>()
+        .expect("AppState must be registered as app data");
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| {
+            ApiError::Unauthorized("You are not logged in, please provide a token".to_string())
+        })?;
+
+    let claims = decode::<TokenClaims>(
+        token,
+        &DecodingKey::from_secret(data.jwt_secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|e| match e.kind() {
+        ErrorKind::ExpiredSignature => ApiError::Unauthorized("Token has expired".to_string()),
+        _ => ApiError::Unauthorized("Invalid token".to_string()),
+    })?
+    .claims;
+
+    Ok(AuthenticatedUser {
+        user_id: claims.sub,
+    })
+}
@@ -0,0 +1,373 @@
+use std::{future::Future, str::FromStr, time::Duration};
+
+use log::LevelFilter;
+use sqlx::{
+    migrate::{MigrateError, Migration, Migrator},
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, PgPool,
+};
+#[cfg(feature = "sqlite")]
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// The schema for SQLite, which cannot run the Postgres migrations.
+#[cfg(feature = "sqlite")]
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+/// A pool for the database `DATABASE_URL` names: SQLite for `sqlite:` URLs
+/// when built with the `sqlite` feature, Postgres otherwise.
+pub enum Database {
+    Postgres(PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite {
+        pool: SqlitePool,
+        /// Kept to decide whether the unique text index should exist, as
+        /// SQLite cannot turn it off per connection.
+        unique_text: bool,
+    },
+}
+
+impl Database {
+    /// Applies every pending embedded migration and returns the ones that ran.
+    pub async fn run_migrations(&self) -> Result<Vec<&'static Migration>, MigrateError> {
+        match self {
+            Database::Postgres(pool) => run_migrations(pool).await,
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite { pool, unique_text } => {
+                run_sqlite_migrations(pool, *unique_text).await
+            }
+        }
+    }
+
+    pub async fn close(&self) {
+        match self {
+            Database::Postgres(pool) => pool.close().await,
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite { pool, .. } => pool.close().await,
+        }
+    }
+}
+
+/// A timestamp as SQLite stores it: UTC RFC 3339 with milliseconds, always the
+/// same width so that comparing the text compares the times.
+#[cfg(feature = "sqlite")]
+pub fn sqlite_timestamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// Whether `database_url` points at SQLite rather than Postgres.
+fn is_sqlite_url(database_url: &str) -> bool {
+    database_url.starts_with("sqlite:")
+}
+
+/// The migrations of `migrator` that are not in `applied`, in order.
+fn pending(migrator: &'static Migrator, applied: &[i64]) -> Vec<&'static Migration> {
+    migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .collect()
+}
+
+async fn run_migrations(pool: &PgPool) -> Result<Vec<&'static Migration>, MigrateError> {
+    // The table does not exist yet on a fresh database, so nothing is applied.
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default();
+    let pending = pending(&MIGRATOR, &applied);
+
+    MIGRATOR.run(pool).await?;
+
+    Ok(pending)
+}
+
+/// Like `run_migrations`, then creates or drops the unique index on live text
+/// to match `unique_text`.
+#[cfg(feature = "sqlite")]
+async fn run_sqlite_migrations(
+    pool: &SqlitePool,
+    unique_text: bool,
+) -> Result<Vec<&'static Migration>, MigrateError> {
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default();
+    let pending = pending(&SQLITE_MIGRATOR, &applied);
+
+    SQLITE_MIGRATOR.run(pool).await?;
+
+    let index = if unique_text {
+        "CREATE UNIQUE INDEX IF NOT EXISTS feedbacks_text_lower_idx
+        ON feedbacks (LOWER(text)) WHERE deleted_at IS NULL"
+    } else {
+        "DROP INDEX IF EXISTS feedbacks_text_lower_idx"
+    };
+    sqlx::query(index)
+        .execute(pool)
+        .await
+        .map_err(MigrateError::Execute)?;
+
+    Ok(pending)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    /// Statements slower than this are logged at `warn` under `sqlx::query`.
+    pub slow_query_threshold: Duration,
+    /// Whether the database rejects live feedback whose text matches another's,
+    /// ignoring case. Turned off per connection on Postgres, and by dropping
+    /// the index when migrations run on SQLite.
+    pub unique_text: bool,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            slow_query_threshold: Duration::from_millis(500),
+            unique_text: true,
+        }
+    }
+}
+
+impl PoolSettings {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Builds the settings from `lookup`, falling back to the defaults for
+    /// variables that are unset or fail to parse.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T: FromStr>(value: Option<String>) -> Option<T> {
+            value.and_then(|value| value.trim().parse().ok())
+        }
+
+        let defaults = Self::default();
+        let max_connections = parse(lookup("DATABASE_MAX_CONNECTIONS"))
+            .filter(|max| *max > 0)
+            .unwrap_or(defaults.max_connections);
+        let min_connections: u32 =
+            parse(lookup("DATABASE_MIN_CONNECTIONS")).unwrap_or(defaults.min_connections);
+
+        Self {
+            max_connections,
+            min_connections: min_connections.min(max_connections),
+            acquire_timeout: parse(lookup("DATABASE_ACQUIRE_TIMEOUT_SECS"))
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.acquire_timeout),
+            slow_query_threshold: parse(lookup("SLOW_QUERY_MS"))
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.slow_query_threshold),
+            unique_text: defaults.unique_text,
+        }
+    }
+
+    pub fn options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+    }
+
+    pub async fn connect(&self, database_url: &str) -> Result<Database, sqlx::Error> {
+        if is_sqlite_url(database_url) {
+            #[cfg(feature = "sqlite")]
+            return self
+                .connect_sqlite(database_url)
+                .await
+                .map(|pool| Database::Sqlite {
+                    pool,
+                    unique_text: self.unique_text,
+                });
+
+            #[cfg(not(feature = "sqlite"))]
+            return Err(sqlx::Error::Configuration(
+                "DATABASE_URL is a sqlite: URL, but the backend was built without the sqlite feature"
+                    .into(),
+            ));
+        }
+
+        self.connect_postgres(database_url)
+            .await
+            .map(Database::Postgres)
+    }
+
+    async fn connect_postgres(&self, database_url: &str) -> Result<PgPool, sqlx::Error> {
+        let mut connect_options = PgConnectOptions::from_str(database_url)?;
+        if !self.unique_text {
+            connect_options = connect_options.options([("feedback.unique_text", "off")]);
+        }
+        connect_options
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(LevelFilter::Warn, self.slow_query_threshold);
+
+        self.options().connect_with(connect_options).await
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn connect_sqlite(&self, database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+        let mut connect_options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        connect_options
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(LevelFilter::Warn, self.slow_query_threshold);
+
+        let options = SqlitePoolOptions::new().acquire_timeout(self.acquire_timeout);
+        // Every connection to an in-memory database opens one of its own, so
+        // there must be exactly one, kept open for as long as the pool.
+        let options = if database_url.contains(":memory:") || database_url.contains("mode=memory") {
+            options
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            options
+                .max_connections(self.max_connections)
+                .min_connections(self.min_connections)
+        };
+
+        options.connect_with(connect_options).await
+    }
+}
+
+/// How often `with_retry` re-runs a query that failed for a transient reason.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_attempts: std::env::var("DATABASE_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|attempts| attempts.trim().parse().ok())
+                .unwrap_or(defaults.max_attempts)
+                .max(1),
+            base_delay: std::env::var("DATABASE_RETRY_BASE_DELAY_MS")
+                .ok()
+                .and_then(|ms| ms.trim().parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+        }
+    }
+}
+
+/// Dropped connections and pool timeouts are worth another try; anything the
+/// database itself rejected (constraint violations, bad SQL) is not, except
+/// for the SQLSTATE class 08 connection exceptions.
+fn is_retriable(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_error) => {
+            db_error.code().map_or(false, |code| code.starts_with("08"))
+        }
+        _ => false,
+    }
+}
+
+/// Runs `operation`, retrying transient failures with exponential backoff
+/// until `policy.max_attempts` is reached.
+pub async fn with_retry<F, Fut, T>(policy: RetryPolicy, mut operation: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        match operation().await {
+            Err(e) if attempt < policy.max_attempts && is_retriable(&e) => {
+                let delay = policy.base_delay * 2u32.saturating_pow(attempt - 1);
+                tracing::warn!(attempt, error = %e, "retrying transient database error");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Checks that the pool can serve a query and that the newest embedded
+/// migration has been applied, returning why not otherwise.
+pub async fn check_ready(pool: &PgPool) -> Result<(), String> {
+    sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("database is unreachable: {}", e))?;
+
+    let Some(latest) = MIGRATOR.iter().map(|migration| migration.version).max() else {
+        return Ok(());
+    };
+
+    let applied: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM _sqlx_migrations WHERE version = $1 AND success)",
+    )
+    .bind(latest)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("could not read applied migrations: {}", e))?;
+
+    if applied {
+        Ok(())
+    } else {
+        Err(format!("migration {} has not been applied", latest))
+    }
+}
+
+/// `check_ready` for SQLite and its migrations.
+#[cfg(feature = "sqlite")]
+pub async fn check_sqlite_ready(pool: &SqlitePool) -> Result<(), String> {
+    sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("database is unreachable: {}", e))?;
+
+    let Some(latest) = SQLITE_MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+    else {
+        return Ok(());
+    };
+
+    let applied: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM _sqlx_migrations WHERE version = $1 AND success)",
+    )
+    .bind(latest)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("could not read applied migrations: {}", e))?;
+
+    if applied {
+        Ok(())
+    } else {
+        Err(format!("migration {} has not been applied", latest))
+    }
+}
@@ -0,0 +1,191 @@
+use actix_web::{
+    error::{InternalError, JsonPayloadError, PathError},
+    http::{header, StatusCode},
+    HttpRequest, HttpResponse, ResponseError,
+};
+use common::{ApiResponse, ResponseStatus, ValidationErrors};
+use serde_json::json;
+use std::fmt;
+
+const UNIQUE_VIOLATION: &str = "23505";
+/// SQLite's extended result code for a failed unique index.
+const SQLITE_CONSTRAINT_UNIQUE: &str = "2067";
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    FeedbackNotFound(uuid::Uuid),
+    Unauthorized(String),
+    Forbidden(String),
+    NotAcceptable(String),
+    Conflict(String),
+    TooManyRequests(u64),
+    /// Another feedback already has the same text, ignoring case. Carries its
+    /// id when it is known.
+    Duplicate(Option<uuid::Uuid>),
+    ProbableDuplicate(uuid::Uuid),
+    Validation(String),
+    InvalidFields(ValidationErrors),
+    /// The route's time budget ran out.
+    Timeout(std::time::Duration),
+    /// A fault on our side other than the database; the message is safe to
+    /// show and the cause is logged where it happened.
+    Internal(String),
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::NotFound(message) => write!(f, "{}", message),
+            ApiError::FeedbackNotFound(id) => write!(f, "Feedback with ID: {} not found", id),
+            ApiError::Unauthorized(message) => write!(f, "{}", message),
+            ApiError::Forbidden(message) => write!(f, "{}", message),
+            ApiError::NotAcceptable(message) => write!(f, "{}", message),
+            ApiError::Conflict(message) => write!(f, "{}", message),
+            ApiError::TooManyRequests(retry_after) => write!(
+                f,
+                "Too many requests, please try again in {} seconds",
+                retry_after
+            ),
+            ApiError::Duplicate(_) => write!(f, "Feedback with that text already exists"),
+            ApiError::ProbableDuplicate(id) => {
+                write!(f, "Feedback looks like a duplicate of {}", id)
+            }
+            ApiError::Validation(message) => write!(f, "{}", message),
+            ApiError::InvalidFields(errors) => write!(f, "{}", errors),
+            ApiError::Timeout(budget) => write!(
+                f,
+                "Request did not complete within {} ms",
+                budget.as_millis()
+            ),
+            ApiError::Internal(message) => write!(f, "{}", message),
+            ApiError::Database(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) | ApiError::FeedbackNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            ApiError::Conflict(_) | ApiError::Duplicate(_) | ApiError::ProbableDuplicate(_) => {
+                StatusCode::CONFLICT
+            }
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Validation(_) | ApiError::InvalidFields(_) => StatusCode::BAD_REQUEST,
+            ApiError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Internal(_) | ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.response_with_message(self.to_string())
+    }
+}
+
+impl ApiError {
+    /// The error's response with `message` in place of its own, e.g. a
+    /// translation of it.
+    pub fn response_with_message(&self, message: String) -> HttpResponse {
+        let status = match self {
+            ApiError::Database(_) | ApiError::Timeout(_) | ApiError::Internal(_) => {
+                ResponseStatus::Error
+            }
+            _ => ResponseStatus::Fail,
+        };
+
+        let mut response = HttpResponse::build(self.status_code());
+
+        if let ApiError::TooManyRequests(retry_after) = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+
+        if let ApiError::InvalidFields(errors) = self {
+            return response.json(json!({
+                "status": status,
+                "message": message,
+                "errors": errors.errors
+            }));
+        }
+
+        if let ApiError::ProbableDuplicate(id) | ApiError::Duplicate(Some(id)) = self {
+            return response.json(json!({
+                "status": status,
+                "message": message,
+                "duplicate_id": id
+            }));
+        }
+
+        response.json(match self {
+            ApiError::Database(_) | ApiError::Timeout(_) | ApiError::Internal(_) => {
+                ApiResponse::<()>::error(message)
+            }
+            _ => ApiResponse::fail(message),
+        })
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::InvalidFields(errors)
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(db_error) = &e {
+            if matches!(
+                db_error.code().as_deref(),
+                Some(UNIQUE_VIOLATION | SQLITE_CONSTRAINT_UNIQUE)
+            ) {
+                return ApiError::Duplicate(None);
+            }
+        }
+
+        ApiError::Database(e)
+    }
+}
+
+/// Turns JSON body extraction failures into the API's JSON error shape: a 415
+/// for a missing or wrong `Content-Type`, a 413 for a body over the configured
+/// limit and a 400 naming the problem for a body that does not parse.
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let (status, message) = match &err {
+        JsonPayloadError::ContentType => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Content-Type must be application/json".to_string(),
+        ),
+        JsonPayloadError::OverflowKnownLength { limit, .. }
+        | JsonPayloadError::Overflow { limit } => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request body must be at most {} bytes", limit),
+        ),
+        JsonPayloadError::Deserialize(e) => {
+            (StatusCode::BAD_REQUEST, format!("Invalid JSON body: {}", e))
+        }
+        other => (other.status_code(), other.to_string()),
+    };
+
+    let response = HttpResponse::build(status).json(if status.is_server_error() {
+        ApiResponse::<()>::error(message)
+    } else {
+        ApiResponse::fail(message)
+    });
+
+    InternalError::from_response(err, response).into()
+}
+
+/// Makes malformed path parameters a JSON 400 instead of actix's plain-text
+/// 404. Every id in the API's paths is a UUID.
+pub fn path_error_handler(err: PathError, req: &HttpRequest) -> actix_web::Error {
+    let message = match req.match_info().iter().next() {
+        Some((name, value)) => format!("{} must be a valid UUID, got {}", name, value),
+        None => format!("Invalid path: {}", err),
+    };
+
+    ApiError::Validation(message).into()
+}
@@ -0,0 +1,33 @@
+/// Behavior deployments switch on or off, read once at startup so handlers
+/// consult `AppState` instead of the environment. Everything is off unless
+/// its variable is `1` or `true`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeatureFlags {
+    /// `ALLOW_DESTRUCTIVE`: enables `DELETE /feedbacks/all`, which also needs
+    /// a `WIPE_CONFIRM_TOKEN`.
+    pub allow_destructive: bool,
+    /// `ALLOW_DUPLICATE_TEXT`: lets live feedback share text, which the
+    /// database otherwise rejects ignoring case.
+    pub allow_duplicate_text: bool,
+    /// `DEDUPE_BY_DEFAULT`: rejects near-duplicate feedback on create unless
+    /// the request passes `dedupe=false`.
+    pub dedupe_by_default: bool,
+}
+
+impl FeatureFlags {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Builds the flags from `lookup`, treating unset variables as off.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let enabled =
+            |name: &str| lookup(name).map_or(false, |value| matches!(value.trim(), "1" | "true"));
+
+        Self {
+            allow_destructive: enabled("ALLOW_DESTRUCTIVE"),
+            allow_duplicate_text: enabled("ALLOW_DUPLICATE_TEXT"),
+            dedupe_by_default: enabled("DEDUPE_BY_DEFAULT"),
+        }
+    }
+}
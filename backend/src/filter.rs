@@ -0,0 +1,81 @@
+use std::{
+    future::{ready, Ready},
+    ops::Deref,
+};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::{error::ApiError, schema::FilterOptions, AppState};
+
+/// List query parameters that have passed every check, along with the
+/// resolved pagination and ordering. Extracting it rejects the request with a
+/// 400 before the handler runs.
+#[derive(Debug)]
+pub struct ValidatedFilter {
+    options: FilterOptions,
+    pub page: usize,
+    pub limit: usize,
+    pub offset: usize,
+    pub sort_column: Option<&'static str>,
+    pub sort_order: &'static str,
+}
+
+impl ValidatedFilter {
+    pub fn new(options: FilterOptions, max_page_size: usize) -> Result<Self, String> {
+        options.validate()?;
+
+        let limit = options.limit.unwrap_or(10).clamp(1, max_page_size.max(1));
+        let page = options.page.unwrap_or(1).max(1);
+        // Saturate rather than overflow on absurd page numbers; Postgres just
+        // returns an empty page for an offset past the end.
+        let offset = (page - 1).saturating_mul(limit).min(i64::MAX as usize);
+
+        let filter = Self {
+            sort_column: options.sort_column()?,
+            sort_order: options.sort_order()?,
+            options,
+            page,
+            limit,
+            offset,
+        };
+
+        if filter.after.is_some() && !filter.cursor_ordering() {
+            return Err("after can only be used when sorting by id in ascending order".to_string());
+        }
+
+        Ok(filter)
+    }
+
+    /// True when rows come back in ascending id order, the only ordering the
+    /// `after` cursor works with.
+    pub fn cursor_ordering(&self) -> bool {
+        self.search_term().is_none()
+            && matches!(self.sort_column, None | Some("id"))
+            && self.sort_order == "ASC"
+    }
+}
+
+impl Deref for ValidatedFilter {
+    type Target = FilterOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.options
+    }
+}
+
+impl FromRequest for ValidatedFilter {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let data = req
+            .app_data::<web::Data<AppState>>()
+            .expect("AppState must be registered as app data");
+
+        ready(
+            FilterOptions::from_query(req.query_string())
+                .and_then(|options| Self::new(options, data.max_page_size))
+                .map_err(ApiError::Validation),
+        )
+    }
+}
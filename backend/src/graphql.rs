@@ -0,0 +1,170 @@
+use actix_web::{get, post, web, HttpResponse};
+use async_graphql::{http::GraphiQLSource, Context, EmptySubscription, Object, Schema};
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use common::FeedbackEvent;
+
+use crate::{
+    auth::AuthenticatedUser,
+    error::ApiError,
+    filter::ValidatedFilter,
+    model::FeedbackModel,
+    schema::{
+        normalize_category, sanitize_text, CreateFeedbackSchema, FilterOptions,
+        UpdateFeedbackSchema,
+    },
+    AppState,
+};
+
+pub type FeedbackSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn schema() -> FeedbackSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish()
+}
+
+fn app_state<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a web::Data<AppState>> {
+    ctx.data::<web::Data<AppState>>()
+}
+
+fn require_user<'a>(ctx: &Context<'a>) -> Result<&'a AuthenticatedUser, ApiError> {
+    ctx.data_opt::<AuthenticatedUser>().ok_or_else(|| {
+        ApiError::Unauthorized("You are not logged in, please provide a token".to_string())
+    })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn feedbacks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: usize,
+        #[graphql(default = 0)] offset: usize,
+        min_rating: Option<i32>,
+    ) -> async_graphql::Result<Vec<FeedbackModel>> {
+        let data = app_state(ctx)?;
+        let opts = FilterOptions {
+            min_rating,
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let mut filter =
+            ValidatedFilter::new(opts, data.max_page_size).map_err(ApiError::Validation)?;
+        filter.offset = offset.min(i64::MAX as usize);
+
+        Ok(data.repo.list(&filter).await?)
+    }
+
+    async fn feedback(
+        &self,
+        ctx: &Context<'_>,
+        id: uuid::Uuid,
+    ) -> async_graphql::Result<Option<FeedbackModel>> {
+        let data = app_state(ctx)?;
+
+        Ok(data.repo.get(id).await?)
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_feedback(
+        &self,
+        ctx: &Context<'_>,
+        text: String,
+        rating: Option<i32>,
+        category: Option<String>,
+        author_email: Option<String>,
+    ) -> async_graphql::Result<FeedbackModel> {
+        let user = require_user(ctx)?;
+        let data = app_state(ctx)?;
+
+        let body = CreateFeedbackSchema {
+            text: sanitize_text(&text),
+            rating,
+            category: category.as_deref().map(normalize_category),
+            author_email,
+            metadata: None,
+        };
+        body.validate(data.max_text_length)
+            .map_err(ApiError::from)?;
+
+        let feedback = data
+            .repo
+            .create(&body, data.default_rating, &user.user_id)
+            .await?;
+
+        data.publish(FeedbackEvent::Created(feedback.clone().into()));
+
+        Ok(feedback)
+    }
+
+    async fn update_feedback(
+        &self,
+        ctx: &Context<'_>,
+        id: uuid::Uuid,
+        text: Option<String>,
+        rating: Option<i32>,
+        category: Option<String>,
+    ) -> async_graphql::Result<FeedbackModel> {
+        let user = require_user(ctx)?;
+        let data = app_state(ctx)?;
+
+        let body = UpdateFeedbackSchema {
+            text: text.as_deref().map(sanitize_text),
+            rating,
+            category: category.as_deref().map(normalize_category),
+            updated_at: None,
+        };
+        body.validate(data.max_text_length)
+            .map_err(ApiError::from)?;
+
+        let feedback = data.repo.update(id, &body, &user.user_id).await?;
+
+        data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+        Ok(feedback)
+    }
+
+    async fn delete_feedback(
+        &self,
+        ctx: &Context<'_>,
+        id: uuid::Uuid,
+    ) -> async_graphql::Result<bool> {
+        let user = require_user(ctx)?;
+        let data = app_state(ctx)?;
+
+        data.repo.delete(id, &user.user_id).await?;
+
+        data.publish(FeedbackEvent::Deleted { id });
+
+        Ok(true)
+    }
+}
+
+#[post("/graphql")]
+pub async fn graphql_handler(
+    schema: web::Data<FeedbackSchema>,
+    data: web::Data<AppState>,
+    user: Option<AuthenticatedUser>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner().data(data);
+
+    if let Some(user) = user {
+        request = request.data(user);
+    }
+
+    schema.execute(request).await.into()
+}
+
+#[get("/graphql")]
+pub async fn graphiql_handler(data: web::Data<AppState>) -> HttpResponse {
+    let endpoint = format!("{}/graphql", data.api_scope);
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(GraphiQLSource::build().endpoint(&endpoint).finish())
+}
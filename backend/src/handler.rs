@@ -1,205 +1,1807 @@
 use crate::{
+    auth::AuthenticatedUser,
+    error::ApiError,
+    filter::ValidatedFilter,
+    idempotency::{Claim, IDEMPOTENCY_KEY_HEADER},
+    metrics::metrics_handler,
     model::FeedbackModel,
-    schema::{CreateFeedbackSchema, FilterOptions, UpdateFeedbackSchema},
+    negotiate::ResponseFormat,
+    openapi,
+    rate_limit::CreateRateLimit,
+    repository::{missing_ids, FeedbackRepository, RatingOrder},
+    schema::{
+        AnomalyOptions, BatchDeleteSchema, BulkRatingSchema, CreateFeedbackSchema,
+        CreateFeedbackWithReplySchema, CreateOptions, CreateReplySchema, FetchByIdsSchema,
+        FieldsOptions, FilterOptions, ImportRow, RankedOptions, RecentOptions, SimilarOptions,
+        TimeZoneOptions, TrendOptions, UpdateFeedbackSchema, VoteSchema, WipeOptions,
+        ANOMALY_BASELINE_DAYS, DEFAULT_RANKED_LIMIT, DEFAULT_SIMILAR_LIMIT, MAX_RATING, MIN_RATING,
+    },
+    sse::feedback_events_handler,
+    stats_cache::FeedbackStats,
+    timeout::RouteTimeout,
+    timezone::DisplayZone,
+    ws::feedback_ws_handler,
     AppState,
 };
-use actix_web::{delete, get, patch, post, web, HttpResponse, Responder};
+use actix_multipart::Multipart;
+use actix_web::{
+    delete, error, get, head, http::header, patch, post, put, rt, web, HttpMessage, HttpRequest,
+    HttpResponse, Responder,
+};
 use chrono::prelude::*;
+use common::{
+    ApiResponse, Feedback, FeedbackAnomalyResponse, FeedbackBulkUpdateResponse,
+    FeedbackCategoryResponse, FeedbackCountResponse, FeedbackEvent, FeedbackImportResponse,
+    FeedbackStatsResponse, FeedbackTrendResponse, FeedbackWipeResponse, ImportRowError, Page,
+    ResponseStatus, SimilarFeedback, ValidationErrors,
+};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use utoipa_swagger_ui::SwaggerUi;
 
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "Service and database are healthy"),
+        (status = 503, description = "Database is unreachable")
+    )
+)]
 #[get("/healthchecker")]
-async fn health_checker_handler() -> impl Responder {
+async fn health_checker_handler(data: web::Data<AppState>) -> impl Responder {
     const MESSAGE: &str = "Build API with Rust, SQLX, Postgres,and Actix Web";
 
-    HttpResponse::Ok().json(json!({
-        "message": MESSAGE,
-        "timestamp": Utc::now(),
-    }))
+    let start = Instant::now();
+    let result = data.repo.ping().await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "status": ResponseStatus::Success,
+            "message": MESSAGE,
+            "database_latency_ms": latency_ms,
+            "timestamp": Utc::now(),
+        })),
+        Err(_) => HttpResponse::ServiceUnavailable().json(json!({
+            "status": ResponseStatus::Error,
+            "message": MESSAGE,
+            "database_latency_ms": latency_ms,
+            "timestamp": Utc::now(),
+        })),
+    }
+}
+
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "The process is running")
+    )
+)]
+#[get("/livez")]
+async fn liveness_handler() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": "alive" }))
 }
 
-#[get("/feedbacks")]
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "The database is reachable and fully migrated"),
+        (status = 503, description = "The database is unreachable, slow or not migrated")
+    )
+)]
+#[get("/readyz")]
+async fn readiness_handler(data: web::Data<AppState>) -> HttpResponse {
+    const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+    let result = match tokio::time::timeout(READINESS_TIMEOUT, data.repo.check_ready()).await {
+        Ok(result) => result,
+        Err(_) => Err(format!(
+            "database did not respond within {} seconds",
+            READINESS_TIMEOUT.as_secs()
+        )),
+    };
+
+    match result {
+        Ok(()) => HttpResponse::Ok().json(json!({ "status": "ready" })),
+        Err(message) => HttpResponse::ServiceUnavailable().json(json!({
+            "status": "not_ready",
+            "message": message
+        })),
+    }
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(FilterOptions, TimeZoneOptions),
+    responses(
+        (status = 200, description = "Paginated feedback list, as XML when Accept asks for it", body = FeedbackListResponse),
+        (status = 400, description = "Invalid filter options or time zone", body = ErrorResponse),
+        (status = 406, description = "Accept allows neither JSON nor XML", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks", wrap = "RouteTimeout")]
 pub async fn feedback_list_handler(
-    opts: web::Query<FilterOptions>,
+    req: HttpRequest,
+    opts: ValidatedFilter,
+    zone: DisplayZone,
+    format: ResponseFormat,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let ValidatedFilter { page, limit, .. } = opts;
+
+    let feedbacks = data.repo.list(&opts).await?;
+    let total = data.repo.count(&opts).await?;
+
+    let next_cursor = if opts.cursor_ordering() && feedbacks.len() == limit {
+        feedbacks.last().map(|feedback| feedback.id)
+    } else {
+        None
+    };
+
+    let feedbacks = Page::new(feedbacks, total, page, limit)
+        .with_next_cursor(next_cursor)
+        .map(|feedback| {
+            let mut feedback = match opts.preview_len {
+                Some(preview_len) => feedback.preview(preview_len),
+                None => json!(feedback),
+            };
+            zone.apply(&mut feedback);
+            feedback
+        });
+
+    let mut response = HttpResponse::Ok();
+
+    // Page links only make sense for offset pagination.
+    if opts.after.is_none() {
+        response.insert_header((header::LINK, pagination_links(&req, &feedbacks)));
+    }
+
+    format.respond(response, &ApiResponse::success(feedbacks))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(RecentOptions, TimeZoneOptions),
+    responses(
+        (status = 200, description = "Feedback updated in the last `days` days, newest first", body = FeedbackListResponse),
+        (status = 400, description = "Invalid time zone", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/recent", wrap = "RouteTimeout")]
+async fn recent_feedback_handler(
+    opts: web::Query<RecentOptions>,
+    zone: DisplayZone,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let days = opts.days();
+    let limit = opts.limit.unwrap_or(10).clamp(1, data.max_page_size);
+    let page = opts.page.unwrap_or(1).max(1);
+    let offset = (page - 1).saturating_mul(limit).min(i64::MAX as usize);
+
+    let feedbacks = data.repo.recent(days, limit, offset).await?;
+    let total = data.repo.count_recent(days).await?;
+
+    let feedbacks = Page::new(feedbacks, total, page, limit).map(|feedback| {
+        let mut feedback = json!(feedback);
+        zone.apply(&mut feedback);
+        feedback
+    });
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(feedbacks)))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(RankedOptions),
+    responses(
+        (status = 200, description = "Lowest-rated feedback first, newest first within a rating", body = FeedbackListResponse)
+    )
+)]
+#[get("/feedbacks/worst", wrap = "RouteTimeout")]
+async fn worst_feedback_handler(
+    opts: web::Query<RankedOptions>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = opts
+        .limit
+        .unwrap_or(DEFAULT_RANKED_LIMIT)
+        .clamp(1, data.max_page_size);
+
+    let feedbacks = data.repo.ranked(RatingOrder::Lowest, limit).await?;
+
+    Ok(ranked_response(feedbacks, limit))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(RankedOptions),
+    responses(
+        (status = 200, description = "Highest-rated feedback first, newest first within a rating", body = FeedbackListResponse)
+    )
+)]
+#[get("/feedbacks/best", wrap = "RouteTimeout")]
+async fn best_feedback_handler(
+    opts: web::Query<RankedOptions>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = opts
+        .limit
+        .unwrap_or(DEFAULT_RANKED_LIMIT)
+        .clamp(1, data.max_page_size);
+
+    let feedbacks = data.repo.ranked(RatingOrder::Highest, limit).await?;
+
+    Ok(ranked_response(feedbacks, limit))
+}
+
+/// A single-page list response, for rankings that are not paginated.
+fn ranked_response(feedbacks: Vec<FeedbackModel>, limit: usize) -> HttpResponse {
+    let total = feedbacks.len() as i64;
+
+    HttpResponse::Ok().json(ApiResponse::success(Page::new(feedbacks, total, 1, limit)))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(TrendOptions),
+    responses(
+        (status = 200, description = "Count and average rating of feedback created in each bucket over the last `days` days, oldest first; empty buckets are left out", body = FeedbackTrendResponse),
+        (status = 400, description = "Unknown bucket", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/trend", wrap = "RouteTimeout")]
+async fn feedback_trend_handler(
+    opts: web::Query<TrendOptions>,
     data: web::Data<AppState>,
-) -> impl Responder {
-    let limit = opts.limit.unwrap_or(10);
-    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+) -> Result<HttpResponse, ApiError> {
+    let bucket = opts.bucket().map_err(ApiError::Validation)?;
+    let days = opts.days();
+
+    let trend = data.repo.trend(bucket, days).await?;
+
+    Ok(HttpResponse::Ok().json(FeedbackTrendResponse {
+        status: ResponseStatus::Success,
+        bucket: bucket.as_str().to_string(),
+        trend,
+    }))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    responses(
+        (status = 200, description = "Count and average rating of feedback in each category, largest first; feedback without a category is counted as `uncategorized`", body = FeedbackCategoryResponse)
+    )
+)]
+#[get("/feedbacks/by-category", wrap = "RouteTimeout")]
+async fn feedback_by_category_handler(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let categories = data.repo.category_counts().await?;
+
+    Ok(HttpResponse::Ok().json(FeedbackCategoryResponse {
+        status: ResponseStatus::Success,
+        categories,
+    }))
+}
 
-    let query_result = sqlx::query_as!(
-        FeedbackModel,
-        "SELECT * FROM feedbacks ORDER by id LIMIT $1 OFFSET $2",
-        limit as i64,
-        offset as i64
+/// Flags a burst of lowest-rated feedback: the count in the last
+/// `window_mins` is compared with the average per window over the week
+/// before it, times `ANOMALY_SPIKE_MULTIPLIER`.
+///
+/// Soft-deleted feedback still counts, so moderating a spike away does not
+/// hide it. The baseline is treated as at least one per window, so a single
+/// low rating after a quiet week is not reported.
+#[utoipa::path(
+    tag = "feedback",
+    params(AnomalyOptions),
+    responses(
+        (status = 200, description = "Lowest-rated feedback in the window against the threshold", body = FeedbackAnomalyResponse)
     )
-    .fetch_all(&data.db)
-    .await;
+)]
+#[get("/feedbacks/anomalies", wrap = "RouteTimeout")]
+async fn feedback_anomalies_handler(
+    opts: web::Query<AnomalyOptions>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let window_mins = opts.window_mins();
+
+    let counts = data
+        .repo
+        .low_rating_counts(window_mins, ANOMALY_BASELINE_DAYS)
+        .await?;
+
+    let baseline_windows = (ANOMALY_BASELINE_DAYS * 24 * 60) as f64 / window_mins as f64;
+    let baseline = counts.baseline as f64 / baseline_windows;
+    let threshold = data.anomaly_multiplier * baseline.max(1.0);
+
+    Ok(HttpResponse::Ok().json(FeedbackAnomalyResponse {
+        status: ResponseStatus::Success,
+        window_mins,
+        count: counts.current,
+        baseline: (baseline * 100.0).round() / 100.0,
+        threshold: (threshold * 100.0).round() / 100.0,
+        anomalous: counts.current as f64 > threshold,
+    }))
+}
+
+/// Builds an RFC 8288 `Link` header value pointing at the first, previous,
+/// next and last pages, keeping every other query parameter as it was.
+fn pagination_links<T>(req: &HttpRequest, current: &Page<T>) -> String {
+    let (page, limit) = (current.page, current.limit);
+    let filters: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or_default();
+            !pair.is_empty() && name != "page" && name != "limit"
+        })
+        .collect();
+    let last = current.total_pages().max(1);
+
+    let link = |page: usize, rel: &str| {
+        let mut query = filters.clone();
+        let page = format!("page={}", page);
+        let limit = format!("limit={}", limit);
+        query.push(&page);
+        query.push(&limit);
+
+        format!("<{}?{}>; rel=\"{}\"", req.path(), query.join("&"), rel)
+    };
+
+    let mut links = vec![link(1, "first")];
 
-    if query_result.is_err() {
-        return HttpResponse::InternalServerError().json(json!({
-            "status": "error",
-            "message": "Something bad happened while fetching all feedback items"
-        }));
+    if page > 1 {
+        links.push(link((page - 1).min(last), "prev"));
     }
 
-    let feedbacks = query_result.unwrap();
+    if page < last {
+        links.push(link(page + 1, "next"));
+    }
+
+    links.push(link(last, "last"));
+
+    links.join(", ")
+}
+
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Same filters as the list endpoint, but only the count is computed.
+#[utoipa::path(
+    tag = "feedback",
+    params(FilterOptions),
+    responses(
+        (status = 200, description = "Number of matching feedback, also sent as X-Total-Count", body = FeedbackCountResponse),
+        (status = 400, description = "Invalid filter options", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/count", wrap = "RouteTimeout")]
+pub async fn feedback_count_handler(
+    opts: ValidatedFilter,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let total = data.repo.count(&opts).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((TOTAL_COUNT_HEADER, total))
+        .json(FeedbackCountResponse {
+            status: ResponseStatus::Success,
+            total,
+        }))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(FilterOptions),
+    responses(
+        (status = 200, description = "Number of matching feedback in the X-Total-Count header"),
+        (status = 400, description = "Invalid filter options")
+    )
+)]
+#[head("/feedbacks", wrap = "RouteTimeout")]
+pub async fn feedback_head_handler(
+    opts: ValidatedFilter,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let total = data.repo.count(&opts).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((TOTAL_COUNT_HEADER, total))
+        .finish())
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    responses(
+        (status = 200, description = "Aggregate rating stats", body = FeedbackStatsResponse)
+    )
+)]
+#[get("/feedbacks/stats", wrap = "RouteTimeout")]
+async fn feedback_stats_handler(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let stats = match data.stats_cache.get() {
+        Ok(stats) => stats,
+        Err(generation) => {
+            let stats = compute_feedback_stats(&data).await?;
+            data.stats_cache.insert(generation, stats.clone());
+            stats
+        }
+    };
 
-    HttpResponse::Ok().json(json!({
-      "status": "success",
-      "results": feedbacks.len(),
-      "feedbacks": feedbacks
+    Ok(HttpResponse::Ok().json(FeedbackStatsResponse {
+        status: ResponseStatus::Success,
+        average: stats.average,
+        count: stats.count,
+        distribution: stats.distribution,
     }))
 }
 
-#[post("/feedbacks/")]
+async fn compute_feedback_stats(data: &AppState) -> Result<FeedbackStats, ApiError> {
+    let rows = data.repo.rating_counts().await?;
+
+    let mut distribution: BTreeMap<u8, i64> = (MIN_RATING..=MAX_RATING)
+        .map(|rating| (rating as u8, 0))
+        .collect();
+    let mut count = 0;
+    let mut sum = 0;
+
+    for (rating, rating_count) in rows {
+        distribution.insert(rating as u8, rating_count);
+        count += rating_count;
+        sum += rating as i64 * rating_count;
+    }
+
+    let average = if count > 0 {
+        (sum as f64 / count as f64 * 100.0).round() / 100.0
+    } else {
+        0.0
+    };
+
+    Ok(FeedbackStats {
+        average,
+        count,
+        distribution,
+    })
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    request_body = CreateFeedbackSchema,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the original response when the same user retries the same body with the key"),
+        CreateOptions
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Feedback created", body = FeedbackResponse),
+        (status = 400, description = "Invalid feedback", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 409, description = "Feedback with the same text already exists, or is too similar to existing feedback and dedupe was requested, and `duplicate_id` names it; or the Idempotency-Key is in use or was used with a different body", body = ErrorResponse),
+        (status = 429, description = "Create rate limit exceeded", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/", wrap = "RouteTimeout")]
 async fn create_feedback_handler(
+    req: HttpRequest,
+    opts: web::Query<CreateOptions>,
     body: web::Json<CreateFeedbackSchema>,
     data: web::Data<AppState>,
-) -> impl Responder {
-    let query_result = sqlx::query_as!(
-        FeedbackModel,
-        "INSERT INTO feedbacks (text, rating) VALUES ($1, $2) RETURNING *",
-        body.text.to_string(),
-        body.rating
-    )
-    .fetch_one(&data.db)
-    .await;
-
-    match query_result {
-        Ok(feedback) => {
-            let response = json!({
-                "status": "success",
-                "data": {
-                    "feedback": feedback
-                }
-            });
-
-            HttpResponse::Ok().json(response)
+    user: AuthenticatedUser,
+    _rate_limit: CreateRateLimit,
+) -> Result<HttpResponse, ApiError> {
+    body.validate(data.max_text_length)?;
+
+    let idempotency_key = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let reservation = match idempotency_key {
+        Some(key) => match data.idempotency.claim(&user.user_id, key, json!(*body))? {
+            Claim::Replay(feedback) => {
+                return Ok(feedback_created_response(&data.api_scope, &feedback))
+            }
+            Claim::New(reservation) => Some(reservation),
+        },
+        None => None,
+    };
+
+    if opts.dedupe.unwrap_or(data.features.dedupe_by_default) {
+        if let Some(duplicate_id) = data
+            .repo
+            .most_similar(&body.text, data.dedupe_threshold)
+            .await?
+        {
+            return Err(ApiError::ProbableDuplicate(duplicate_id));
+        }
+    }
+
+    let feedback = match data
+        .repo
+        .create(&body, data.default_rating, &user.user_id)
+        .await
+    {
+        Err(ApiError::Duplicate(None)) => {
+            let existing_id = data.repo.find_by_text(&body.text).await?;
+            return Err(ApiError::Duplicate(existing_id));
+        }
+        result => result?,
+    };
+
+    if let Some(reservation) = reservation {
+        reservation.complete(feedback.clone());
+    }
+
+    data.publish(FeedbackEvent::Created(feedback.clone().into()));
+
+    Ok(feedback_created_response(&data.api_scope, &feedback))
+}
+
+fn feedback_created_response(api_scope: &str, feedback: &FeedbackModel) -> HttpResponse {
+    HttpResponse::Created()
+        .insert_header((
+            header::LOCATION,
+            format!("{}/feedbacks/{}", api_scope, feedback.id),
+        ))
+        .json(ApiResponse::success(json!({ "feedback": feedback })))
+}
+
+/// Creates the feedback and its first reply in one transaction, so neither
+/// is stored unless both are.
+#[utoipa::path(
+    tag = "feedback",
+    request_body = CreateFeedbackWithReplySchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Feedback and reply created", body = FeedbackWithReplyResponse),
+        (status = 400, description = "Invalid feedback or reply", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 429, description = "Create rate limit exceeded", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/with-reply", wrap = "RouteTimeout")]
+async fn create_feedback_with_reply_handler(
+    body: web::Json<CreateFeedbackWithReplySchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+    _rate_limit: CreateRateLimit,
+) -> Result<HttpResponse, ApiError> {
+    body.validate(data.max_text_length)?;
+
+    let (feedback, reply) = data
+        .repo
+        .create_with_reply(
+            &body.feedback,
+            &body.reply.text,
+            data.default_rating,
+            &user.user_id,
+        )
+        .await?;
+
+    data.publish(FeedbackEvent::Created(feedback.clone().into()));
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            header::LOCATION,
+            format!("{}/feedbacks/{}", data.api_scope, feedback.id),
+        ))
+        .json(ApiResponse::success(
+            json!({ "feedback": feedback, "reply": reply }),
+        )))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    request_body = Vec<CreateFeedbackSchema>,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback batch created", body = FeedbackListResponse),
+        (status = 400, description = "An item in the batch is invalid", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/batch", wrap = "RouteTimeout")]
+async fn create_feedback_batch_handler(
+    body: web::Json<Vec<CreateFeedbackSchema>>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    if body.is_empty() {
+        return Err(ApiError::Validation(
+            "batch must contain at least one feedback".to_string(),
+        ));
+    }
+
+    let mut errors = ValidationErrors::default();
+    for (index, feedback) in body.iter().enumerate() {
+        if let Err(item_errors) = feedback.validate(data.max_text_length) {
+            for error in item_errors.errors {
+                errors.add(format!("[{}].{}", index, error.field), error.message);
+            }
+        }
+    }
+    errors.into_result()?;
+
+    let feedbacks = data
+        .repo
+        .create_batch(&body, data.default_rating, &user.user_id)
+        .await?;
+
+    for feedback in &feedbacks {
+        data.publish(FeedbackEvent::Created(feedback.clone().into()));
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Page::single(feedbacks))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    responses(
+        (status = 200, description = "All feedback as CSV", content_type = "text/csv", body = String)
+    )
+)]
+#[get("/feedbacks/export.csv", wrap = "RouteTimeout")]
+async fn export_feedback_csv_handler(data: web::Data<AppState>) -> HttpResponse {
+    let (tx, rx) = mpsc::channel(16);
+    let repo = data.repo.clone();
+
+    rt::spawn(async move {
+        if let Err(e) = write_feedback_csv(repo.as_ref(), &tx).await {
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"feedbacks.csv\"",
+        ))
+        .streaming(body)
+}
+
+/// Sends the header and then one chunk per row, so the table is never held in memory.
+async fn write_feedback_csv(
+    repo: &dyn FeedbackRepository,
+    tx: &mpsc::Sender<Result<web::Bytes, actix_web::Error>>,
+) -> Result<(), actix_web::Error> {
+    let mut chunk = csv_record(["id", "text", "rating", "created_at", "updated_at"])?;
+    let mut rows = repo.stream_live();
+
+    loop {
+        if tx.send(Ok(chunk)).await.is_err() {
+            // The client went away; stop reading rows.
+            return Ok(());
+        }
+
+        let Some(feedback) = rows
+            .try_next()
+            .await
+            .map_err(error::ErrorInternalServerError)?
+        else {
+            return Ok(());
+        };
+
+        chunk = csv_record([
+            feedback.id.to_string(),
+            feedback.text,
+            feedback.rating.to_string(),
+            feedback.created_at.to_rfc3339(),
+            feedback.updated_at.to_rfc3339(),
+        ])?;
+    }
+}
+
+fn csv_record<I, T>(record: I) -> Result<web::Bytes, actix_web::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(record)
+        .map_err(error::ErrorInternalServerError)?;
+
+    writer
+        .into_inner()
+        .map(web::Bytes::from)
+        .map_err(error::ErrorInternalServerError)
+}
+
+/// Rows inserted per statement during a CSV import.
+const IMPORT_BATCH_SIZE: usize = 500;
+/// Row errors listed in an import response; the rest are only counted.
+const MAX_IMPORT_ERRORS: usize = 100;
+
+/// Imports feedback from a CSV uploaded as the `file` field of a multipart
+/// body, with a `text` column and optional `rating` and `category` columns.
+///
+/// The upload is parsed as it arrives rather than buffered. Invalid rows, and
+/// rows whose text is already taken, are skipped and reported; the rest are
+/// inserted in one transaction. Imported feedback is not broadcast to
+/// subscribers one by one.
+#[utoipa::path(
+    tag = "feedback",
+    request_body(content = String, description = "Multipart body with the CSV in a `file` field", content_type = "multipart/form-data"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Valid rows imported, invalid ones listed", body = FeedbackImportResponse),
+        (status = 400, description = "No file field, or the CSV has no text column", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/import", wrap = "RouteTimeout")]
+async fn import_feedback_csv_handler(
+    mut payload: Multipart,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let mut file = None;
+    while let Some(field) = payload
+        .try_next()
+        .await
+        .map_err(|e| ApiError::Validation(e.to_string()))?
+    {
+        if field.name() == "file" {
+            file = Some(field);
+            break;
         }
-        Err(e) => {
-            if e.to_string()
-                .contains("duplicate key value violates unique constraint")
-            {
-                HttpResponse::BadRequest().json(json!({
-                    "status": "fail",
-                    "message": "Feedback with that title already exists"
-                }))
-            } else {
-                HttpResponse::InternalServerError().json(json!({
-                    "status": "error",
-                    "message": format!("{:?}", e)
-                }))
+    }
+    let mut file = file.ok_or_else(|| {
+        ApiError::Validation("multipart body must include a file field".to_string())
+    })?;
+
+    // The CSV reader needs a `Send` source, which multipart fields are not, so
+    // chunks are passed to it over a channel while it parses.
+    let (chunks, rx) = mpsc::channel::<std::io::Result<web::Bytes>>(16);
+    let upload = async move {
+        while let Some(chunk) = file.next().await {
+            let chunk =
+                chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()));
+            let failed = chunk.is_err();
+
+            // A closed channel means the import stopped early.
+            if chunks.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+    };
+    let reader = Box::pin(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+    .into_async_read();
+
+    let (_, response) = futures_util::join!(upload, import_feedback_csv(reader, &data, &user));
+    let response = response?;
+
+    data.stats_cache.invalidate();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+async fn import_feedback_csv<R>(
+    reader: R,
+    data: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<FeedbackImportResponse, ApiError>
+where
+    R: futures_util::AsyncRead + Unpin + Send,
+{
+    let csv_error = |e: csv_async::Error| ApiError::Validation(format!("Invalid CSV: {}", e));
+
+    let mut rows = csv_async::AsyncReaderBuilder::new()
+        .trim(csv_async::Trim::All)
+        .create_deserializer(reader);
+
+    if !rows
+        .headers()
+        .await
+        .map_err(csv_error)?
+        .iter()
+        .any(|column| column == "text")
+    {
+        return Err(ApiError::Validation(
+            "CSV must have a text column".to_string(),
+        ));
+    }
+
+    let mut import = data
+        .repo
+        .begin_import(data.default_rating, &user.user_id)
+        .await?;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut batch_rows = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut response = FeedbackImportResponse {
+        status: ResponseStatus::Success,
+        imported: 0,
+        skipped: 0,
+        errors: Vec::new(),
+    };
+
+    let mut records = rows.deserialize::<ImportRow>();
+    let mut row = 0;
+    while let Some(record) = records.next().await {
+        row += 1;
+
+        let feedback = match record {
+            Err(e) if e.is_io_error() => return Err(csv_error(e)),
+            Err(e) => Err(e.to_string()),
+            Ok(record) => {
+                let feedback = CreateFeedbackSchema::from(record);
+                feedback
+                    .validate(data.max_text_length)
+                    .map(|_| feedback)
+                    .map_err(|errors| errors.to_string())
             }
+        };
+
+        match feedback {
+            Ok(feedback) => {
+                batch.push(feedback);
+                batch_rows.push(row);
+            }
+            Err(message) => skip_import_row(&mut response, row, message),
+        }
+
+        if batch.len() == IMPORT_BATCH_SIZE {
+            let duplicates = import.insert(&batch).await?;
+            record_import_batch(&mut response, &batch_rows, &duplicates);
+            batch.clear();
+            batch_rows.clear();
         }
     }
+
+    if !batch.is_empty() {
+        let duplicates = import.insert(&batch).await?;
+        record_import_batch(&mut response, &batch_rows, &duplicates);
+    }
+
+    import.commit().await?;
+
+    // Duplicates are only found when their batch is inserted, after the
+    // invalid rows around them were reported.
+    response.errors.sort_by_key(|error| error.row);
+
+    Ok(response)
+}
+
+fn skip_import_row(response: &mut FeedbackImportResponse, row: u64, message: String) {
+    response.skipped += 1;
+    if response.errors.len() < MAX_IMPORT_ERRORS {
+        response.errors.push(ImportRowError { row, message });
+    }
 }
 
-#[get("/feedbacks/{id}")]
+/// Counts an inserted batch whose rows are `rows`, given the indices into it
+/// of the ones skipped as duplicates.
+fn record_import_batch(response: &mut FeedbackImportResponse, rows: &[u64], duplicates: &[usize]) {
+    response.imported += (rows.len() - duplicates.len()) as u64;
+
+    for &index in duplicates {
+        skip_import_row(response, rows[index], ApiError::Duplicate(None).to_string());
+    }
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    responses(
+        (status = 200, description = "All feedback, one JSON object per line", content_type = "application/x-ndjson", body = Feedback)
+    )
+)]
+#[get("/feedbacks/stream", wrap = "RouteTimeout")]
+async fn stream_feedback_handler(data: web::Data<AppState>) -> HttpResponse {
+    let (tx, rx) = mpsc::channel(16);
+    let repo = data.repo.clone();
+
+    rt::spawn(async move {
+        if let Err(e) = write_feedback_ndjson(repo.as_ref(), &tx).await {
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
+
+/// Sends one line per row as it is read. A database error ends the stream
+/// early, so the client sees a truncated body rather than a buffered error.
+async fn write_feedback_ndjson(
+    repo: &dyn FeedbackRepository,
+    tx: &mpsc::Sender<Result<web::Bytes, actix_web::Error>>,
+) -> Result<(), actix_web::Error> {
+    let mut rows = repo.stream_live();
+
+    while let Some(feedback) = rows
+        .try_next()
+        .await
+        .map_err(error::ErrorInternalServerError)?
+    {
+        let mut line = serde_json::to_vec(&Feedback::from(feedback))
+            .map_err(error::ErrorInternalServerError)?;
+        line.push(b'\n');
+
+        if tx.send(Ok(web::Bytes::from(line))).await.is_err() {
+            // The client went away; stop reading rows.
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id"), FieldsOptions, TimeZoneOptions),
+    responses(
+        (status = 200, description = "Feedback found, as XML when Accept asks for it", body = FeedbackResponse),
+        (status = 304, description = "Feedback matches the If-None-Match ETag"),
+        (status = 400, description = "Unknown field requested or invalid time zone", body = ErrorResponse),
+        (status = 406, description = "Accept allows neither JSON nor XML", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/{id}", wrap = "RouteTimeout")]
 async fn get_feedback_handler(
     path: web::Path<uuid::Uuid>,
+    opts: web::Query<FieldsOptions>,
+    zone: DisplayZone,
+    format: ResponseFormat,
     data: web::Data<AppState>,
-) -> impl Responder {
+    if_none_match: Option<web::Header<header::IfNoneMatch>>,
+) -> Result<HttpResponse, ApiError> {
     let feedback_id = path.into_inner();
+    let fields = opts.selected().map_err(ApiError::Validation)?;
 
-    let query_result = sqlx::query_as!(
-        FeedbackModel,
-        "SELECT * FROM feedbacks WHERE id = $1",
-        feedback_id,
-    )
-    .fetch_one(&data.db)
-    .await;
+    let feedback = data
+        .repo
+        .get(feedback_id)
+        .await?
+        .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))?;
 
-    match query_result {
-        Ok(feedback) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "feedback": feedback
-        })),
-        Err(_) => HttpResponse::NotFound().json(json!({
-            "status": "fail",
-            "message": format!("feedback with ID: {} not found", feedback_id)
-        })),
+    let etag = header::EntityTag::new_strong(feedback.etag());
+    let not_modified = match if_none_match.map(web::Header::into_inner) {
+        Some(header::IfNoneMatch::Any) => true,
+        Some(header::IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        None => false,
+    };
+
+    if not_modified {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(header::ETag(etag))
+            .insert_header((header::VARY, "Accept"))
+            .finish());
     }
+
+    let mut feedback = match fields {
+        Some(fields) => feedback.project(&fields),
+        None => json!(feedback),
+    };
+    zone.apply(&mut feedback);
+
+    let mut response = HttpResponse::Ok();
+    response.insert_header(header::ETag(etag));
+
+    format.respond(
+        response,
+        &ApiResponse::success(json!({ "feedback": feedback })),
+    )
+}
+
+/// Replaces a feedback wholesale.
+///
+/// Unlike `PATCH`, fields left out of the body are reset rather than kept, so
+/// omitting `category` clears it.
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    request_body = CreateFeedbackSchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback replaced", body = FeedbackResponse),
+        (status = 400, description = "Invalid feedback", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[put("/feedbacks/{id}", wrap = "RouteTimeout")]
+async fn replace_feedback_handler(
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<CreateFeedbackSchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    body.validate(data.max_text_length)?;
+
+    let feedback = data
+        .repo
+        .replace(feedback_id, &body, data.default_rating, &user.user_id)
+        .await?
+        .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))?;
+
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
 }
 
-#[patch("/feedbacks/{id}")]
+/// Partially updates a feedback.
+///
+/// Only the fields present in the body change; everything else keeps its
+/// current value. Use `PUT` to replace the whole feedback.
+///
+/// With `Content-Type: application/json-patch+json` the body is instead an
+/// RFC 6902 patch applied to the current feedback; it may only write to
+/// `text`, `rating` and `category`.
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    request_body = UpdateFeedbackSchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback updated", body = FeedbackResponse),
+        (status = 400, description = "Invalid feedback, or a patch writing to a read-only field", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse),
+        (status = 409, description = "Feedback changed since the given updatedAt", body = ErrorResponse)
+    )
+)]
+#[patch("/feedbacks/{id}", wrap = "RouteTimeout")]
 async fn edit_feedback_handler(
+    req: HttpRequest,
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<serde_json::Value>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let body = if is_json_patch(&req) {
+        let patch: json_patch::Patch = serde_json::from_value(body.into_inner())
+            .map_err(|e| ApiError::Validation(format!("Invalid JSON Patch: {}", e)))?;
+        let current = data
+            .repo
+            .get(feedback_id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))?;
+
+        UpdateFeedbackSchema::from_json_patch(&current, &patch).map_err(ApiError::Validation)?
+    } else {
+        serde_json::from_value::<UpdateFeedbackSchema>(body.into_inner())
+            .map_err(|e| ApiError::Validation(format!("Invalid JSON body: {}", e)))?
+    };
+
+    body.validate(data.max_text_length)?;
+
+    let feedback = data.repo.update(feedback_id, &body, &user.user_id).await?;
+
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
+}
+
+fn is_json_patch(req: &HttpRequest) -> bool {
+    matches!(
+        req.mime_type(),
+        Ok(Some(mime)) if mime.type_() == mime::APPLICATION && mime.subtype() == "json-patch"
+    )
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Feedback deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[delete("/feedbacks/{id}", wrap = "RouteTimeout")]
+async fn delete_feedback_handler(
     path: web::Path<uuid::Uuid>,
-    body: web::Json<UpdateFeedbackSchema>,
     data: web::Data<AppState>,
-) -> impl Responder {
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
     let feedback_id = path.into_inner();
 
-    let query_result = sqlx::query_as!(
-        FeedbackModel,
-        "SELECT * FROM feedbacks WHERE id = $1",
-        feedback_id
+    data.repo.delete(feedback_id, &user.user_id).await?;
+
+    data.publish(FeedbackEvent::Deleted { id: feedback_id });
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    request_body = FetchByIdsSchema,
+    responses(
+        (status = 200, description = "Feedback with the given ids, in request order; unknown ids are left out", body = FeedbackListResponse),
+        (status = 400, description = "Too many ids", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/fetch", wrap = "RouteTimeout")]
+async fn fetch_feedback_by_ids_handler(
+    body: web::Json<FetchByIdsSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    if body.ids.len() > data.max_page_size {
+        return Err(ApiError::Validation(format!(
+            "at most {} ids can be fetched at once",
+            data.max_page_size
+        )));
+    }
+
+    let feedbacks = data.repo.get_many(&body.ids).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Page::single(feedbacks))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    request_body = BatchDeleteSchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback deleted; ids that did not match are listed", body = FeedbackBatchDeleteResponse),
+        (status = 400, description = "No ids given", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Strict mode and some ids were not found", body = ErrorResponse)
     )
-    .fetch_one(&data.db)
-    .await;
+)]
+#[post("/feedbacks/batch-delete", wrap = "RouteTimeout")]
+async fn delete_feedback_batch_handler(
+    body: web::Json<BatchDeleteSchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    if body.ids.is_empty() {
+        return Err(ApiError::Validation("ids must not be empty".to_string()));
+    }
 
-    if query_result.is_err() {
-        return HttpResponse::NotFound().json(json!({
-            "status": "fail",
-            "message": format!("Feedback with ID: {} not found", feedback_id)
-        }));
+    let deleted = data
+        .repo
+        .delete_many(&body.ids, body.strict, &user.user_id)
+        .await?;
+
+    let not_found = missing_ids(&body.ids, &deleted);
+
+    for id in &deleted {
+        data.publish(FeedbackEvent::Deleted { id: *id });
     }
 
-    let now = Utc::now();
-    let feedback = query_result.unwrap();
+    Ok(HttpResponse::Ok().json(json!({
+        "status": ResponseStatus::Success,
+        "deleted": deleted.len(),
+        "not_found": not_found
+    })))
+}
 
-    let query_result = sqlx::query_as!(
-        FeedbackModel,
-        "UPDATE feedbacks SET text = $1, rating = $2, updated_at = $3 WHERE id = $4 RETURNING *",
-        body.text.to_owned().unwrap_or(feedback.text),
-        body.rating.to_owned().unwrap_or(feedback.rating),
-        now,
-        feedback_id
+/// Sets the rating of every live feedback in `ids` at once, e.g. when a
+/// moderator re-scores a group of items. Ids that match nothing are skipped.
+#[utoipa::path(
+    tag = "feedback",
+    request_body = BulkRatingSchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Ratings updated", body = FeedbackBulkUpdateResponse),
+        (status = 400, description = "No ids given or rating out of range", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse)
     )
-    .fetch_one(&data.db)
-    .await;
+)]
+#[patch("/feedbacks/bulk", wrap = "RouteTimeout")]
+async fn bulk_rating_handler(
+    body: web::Json<BulkRatingSchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    body.validate()?;
 
-    match query_result {
-        Ok(feedback) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "feedback": feedback
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "status": "error",
-            "message": format!("Error: {:?}", e)
-        })),
+    let updated = data
+        .repo
+        .set_rating(&body.ids, body.rating, &user.user_id)
+        .await?;
+
+    for feedback in &updated {
+        data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+    }
+
+    Ok(HttpResponse::Ok().json(FeedbackBulkUpdateResponse {
+        status: ResponseStatus::Success,
+        updated: updated.len() as u64,
+    }))
+}
+
+/// Permanently removes every feedback, for resetting test and staging
+/// environments. Their replies go with them through the foreign key's
+/// `ON DELETE CASCADE`; votes are columns of the feedback itself.
+///
+/// Answers 404 as if the route did not exist unless the server runs with
+/// `ALLOW_DESTRUCTIVE=true` and a `WIPE_CONFIRM_TOKEN`, which `confirm` must
+/// match. The audit log is kept, and subscribers are told about each removed
+/// feedback as if it had been deleted on its own.
+#[utoipa::path(
+    tag = "feedback",
+    params(WipeOptions),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "All feedback deleted", body = FeedbackWipeResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "confirm does not match", body = ErrorResponse),
+        (status = 404, description = "Destructive endpoints are disabled", body = ErrorResponse)
+    )
+)]
+#[delete("/feedbacks/all", wrap = "RouteTimeout")]
+async fn delete_all_feedback_handler(
+    req: HttpRequest,
+    opts: web::Query<WipeOptions>,
+    data: web::Data<AppState>,
+    user: Result<AuthenticatedUser, ApiError>,
+) -> Result<HttpResponse, ApiError> {
+    let (true, Some(token)) = (data.features.allow_destructive, &data.wipe_confirm_token) else {
+        return not_found_handler(req).await;
+    };
+
+    user?;
+
+    if opts.confirm.as_deref() != Some(token.as_str()) {
+        return Err(ApiError::Forbidden(
+            "confirm does not match the configured token".to_string(),
+        ));
+    }
+
+    let deleted = data.repo.delete_all().await?;
+
+    for id in &deleted {
+        data.publish(FeedbackEvent::Deleted { id: *id });
     }
+
+    tracing::warn!(deleted = deleted.len(), "wiped all feedback");
+
+    Ok(HttpResponse::Ok().json(FeedbackWipeResponse {
+        status: ResponseStatus::Success,
+        deleted: deleted.len() as i64,
+    }))
 }
 
-#[delete("/feedbacks/{id}")]
-async fn delete_feedback_handler(
+/// Erases the personal data of a feedback, e.g. for a GDPR request: the text
+/// becomes a placeholder and the author's email and metadata are dropped,
+/// here and in its audit history. The rating and timestamps are kept so
+/// aggregates do not change. Works on deleted feedback too.
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback anonymized", body = FeedbackResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/{id}/anonymize", wrap = "RouteTimeout")]
+async fn anonymize_feedback_handler(
     path: web::Path<uuid::Uuid>,
     data: web::Data<AppState>,
-) -> impl Responder {
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
     let feedback_id = path.into_inner();
 
-    let rows_affected = sqlx::query!("DELETE FROM feedbacks WHERE id = $1", feedback_id)
-        .execute(&data.db)
-        .await
-        .unwrap()
-        .rows_affected();
+    let feedback = data.repo.anonymize(feedback_id, &user.user_id).await?;
 
-    if rows_affected == 0 {
-        return HttpResponse::NotFound().json(json!({
-            "status": "fail",
-            "message": format!("Feedback with ID: {} not found", feedback_id)
-        }));
-    }
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
 
-    HttpResponse::NoContent().finish()
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
 }
 
-pub fn config(conf: &mut web::ServiceConfig) {
-    let scope = web::scope("/api")
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback restored", body = FeedbackResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse),
+        (status = 409, description = "Feedback is not deleted", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/{id}/restore", wrap = "RouteTimeout")]
+async fn restore_feedback_handler(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let feedback = data.repo.restore(feedback_id, &user.user_id).await?;
+
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    request_body = VoteSchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Vote recorded", body = FeedbackResponse),
+        (status = 400, description = "Invalid vote direction", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/{id}/vote", wrap = "RouteTimeout")]
+async fn vote_feedback_handler(
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<VoteSchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let (upvotes, downvotes) = body.increments().map_err(ApiError::Validation)?;
+
+    let feedback = data
+        .repo
+        .vote(feedback_id, upvotes, downvotes, &user.user_id)
+        .await?;
+
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Feedback with `updated_at` set to now and everything else unchanged", body = FeedbackResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/{id}/touch", wrap = "RouteTimeout")]
+async fn touch_feedback_handler(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let feedback = data.repo.touch(feedback_id, &user.user_id).await?;
+
+    data.publish(FeedbackEvent::Updated(feedback.clone().into()));
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "feedback": feedback }))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    request_body = CreateReplySchema,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Reply created", body = FeedbackReplyResponse),
+        (status = 400, description = "Invalid reply", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[post("/feedbacks/{id}/replies", wrap = "RouteTimeout")]
+async fn create_reply_handler(
+    path: web::Path<uuid::Uuid>,
+    body: web::Json<CreateReplySchema>,
+    data: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    body.validate(data.max_text_length)?;
+
+    let reply = data
+        .repo
+        .create_reply(feedback_id, &body.text, &user.user_id)
+        .await?;
+
+    data.publish(FeedbackEvent::Replied(reply.clone().into()));
+
+    Ok(HttpResponse::Created().json(ApiResponse::success(json!({ "reply": reply }))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    responses(
+        (status = 200, description = "Replies to the feedback, oldest first", body = FeedbackReplyListResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/{id}/replies", wrap = "RouteTimeout")]
+async fn reply_list_handler(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let replies = data.repo.replies(feedback_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Page::single(replies))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id"), SimilarOptions),
+    responses(
+        (status = 200, description = "Other live feedback with text in common, most similar first", body = FeedbackSimilarResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/{id}/similar", wrap = "RouteTimeout")]
+async fn similar_feedback_handler(
+    path: web::Path<uuid::Uuid>,
+    opts: web::Query<SimilarOptions>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+    let limit = opts
+        .limit
+        .unwrap_or(DEFAULT_SIMILAR_LIMIT)
+        .clamp(1, data.max_page_size);
+
+    let feedback = data
+        .repo
+        .get(feedback_id)
+        .await?
+        .ok_or(ApiError::FeedbackNotFound(feedback_id))?;
+
+    let feedbacks: Vec<SimilarFeedback> = data
+        .repo
+        .similar_to(&feedback, limit)
+        .await?
+        .into_iter()
+        .map(|(feedback, similarity)| SimilarFeedback {
+            similarity,
+            feedback: feedback.into(),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Page::single(feedbacks))))
+}
+
+#[utoipa::path(
+    tag = "feedback",
+    params(("id" = uuid::Uuid, Path, description = "Feedback id")),
+    responses(
+        (status = 200, description = "Audit trail of the feedback, oldest first", body = FeedbackHistoryResponse),
+        (status = 404, description = "Feedback not found", body = ErrorResponse)
+    )
+)]
+#[get("/feedbacks/{id}/history", wrap = "RouteTimeout")]
+async fn feedback_history_handler(
+    path: web::Path<uuid::Uuid>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let feedback_id = path.into_inner();
+
+    let entries = data.repo.history(feedback_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(Page::single(entries))))
+}
+
+/// Fallback for every unmatched route so clients get the same JSON error
+/// shape as the rest of the API instead of actix's plain-text 404.
+async fn not_found_handler(req: HttpRequest) -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound(format!(
+        "no route for {} {}",
+        req.method(),
+        req.path()
+    )))
+}
+
+pub const API_VERSION: &str = "v1";
+
+/// The scope every handler is mounted under, e.g. `/api/v1` for the `/api` prefix.
+pub fn api_scope(prefix: &str) -> String {
+    format!("{}/{}", prefix, API_VERSION)
+}
+
+pub fn config(conf: &mut web::ServiceConfig, prefix: &str) {
+    let scope_path = api_scope(prefix);
+    let docs_path = format!("{}/docs", prefix);
+    let docs = SwaggerUi::new(format!("{}/{{_:.*}}", docs_path)).url(
+        format!("{}/openapi.json", docs_path),
+        openapi::api_doc(&scope_path),
+    );
+
+    let scope = web::scope(&scope_path)
         .service(health_checker_handler)
+        .service(liveness_handler)
+        .service(readiness_handler)
         .service(feedback_list_handler)
+        .service(feedback_head_handler)
+        .service(feedback_count_handler)
+        .service(feedback_stats_handler)
+        .service(recent_feedback_handler)
+        .service(worst_feedback_handler)
+        .service(best_feedback_handler)
+        .service(feedback_trend_handler)
+        .service(feedback_by_category_handler)
+        .service(feedback_anomalies_handler)
+        .service(feedback_ws_handler)
+        .service(feedback_events_handler)
         .service(create_feedback_handler)
+        .service(create_feedback_with_reply_handler)
+        .service(create_feedback_batch_handler)
+        .service(fetch_feedback_by_ids_handler)
+        .service(delete_feedback_batch_handler)
+        .service(delete_all_feedback_handler)
+        .service(export_feedback_csv_handler)
+        .service(import_feedback_csv_handler)
+        .service(stream_feedback_handler)
+        .service(bulk_rating_handler)
         .service(get_feedback_handler)
+        .service(replace_feedback_handler)
         .service(edit_feedback_handler)
-        .service(delete_feedback_handler);
+        .service(delete_feedback_handler)
+        .service(restore_feedback_handler)
+        .service(anonymize_feedback_handler)
+        .service(vote_feedback_handler)
+        .service(touch_feedback_handler)
+        .service(create_reply_handler)
+        .service(reply_list_handler)
+        .service(similar_feedback_handler)
+        .service(feedback_history_handler);
+
+    #[cfg(feature = "graphql")]
+    let scope = scope
+        .app_data(web::Data::new(crate::graphql::schema()))
+        .service(crate::graphql::graphql_handler)
+        .service(crate::graphql::graphiql_handler);
+
+    conf.service(docs)
+        .service(web::redirect(docs_path.clone(), format!("{}/", docs_path)))
+        .service(metrics_handler)
+        .service(scope)
+        .default_service(web::to(not_found_handler));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use actix_web::{http::StatusCode, test, App};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::{
+        auth::TokenClaims, features::FeatureFlags, idempotency::IdempotencyCache, metrics::Metrics,
+        rate_limit::RateLimiter, repository::InMemoryFeedbackRepository, stats_cache::StatsCache,
+        timeout::RouteTimeouts,
+    };
+
+    const JWT_SECRET: &str = "test-secret";
+
+    fn app_state() -> web::Data<AppState> {
+        web::Data::new(AppState {
+            repo: Arc::new(InMemoryFeedbackRepository::default()),
+            jwt_secret: JWT_SECRET.to_string(),
+            events: broadcast::channel(16).0,
+            create_limiter: RateLimiter::per_minute(100),
+            idempotency: IdempotencyCache::new(Duration::from_secs(60)),
+            stats_cache: StatsCache::new(Duration::from_secs(60)),
+            webhook: None,
+            max_page_size: 100,
+            max_text_length: 500,
+            default_rating: 3,
+            dedupe_threshold: 0.6,
+            anomaly_multiplier: 3.0,
+            wipe_confirm_token: None,
+            features: FeatureFlags::default(),
+            metrics: Metrics::new(),
+            route_timeouts: RouteTimeouts::from_env(),
+            api_scope: api_scope("/api"),
+        })
+    }
+
+    fn bearer_token() -> String {
+        let now = Utc::now().timestamp() as usize;
+        let claims = TokenClaims {
+            sub: "user-1".to_string(),
+            iat: now,
+            exp: now + 60,
+        };
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+        )
+        .unwrap();
 
-    conf.service(scope);
+        format!("Bearer {}", token)
+    }
+
+    #[actix_web::test]
+    async fn created_feedback_can_be_voted_on_and_has_history() {
+        let data = app_state();
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .configure(|conf| config(conf, "/api")),
+        )
+        .await;
+
+        let created: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/v1/feedbacks/")
+                .insert_header((header::AUTHORIZATION, bearer_token()))
+                .set_json(json!({ "text": "Great service", "rating": 4 }))
+                .to_request(),
+        )
+        .await;
+        let id = created["data"]["feedback"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let duplicate = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/v1/feedbacks/")
+                .insert_header((header::AUTHORIZATION, bearer_token()))
+                .set_json(json!({ "text": "great SERVICE", "rating": 2 }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(duplicate.status(), StatusCode::CONFLICT);
+
+        let voted: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!("/api/v1/feedbacks/{}/vote", id))
+                .insert_header((header::AUTHORIZATION, bearer_token()))
+                .set_json(json!({ "direction": "up" }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(voted["data"]["feedback"]["upvotes"], 1);
+
+        let history: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/v1/feedbacks/{}/history", id))
+                .to_request(),
+        )
+        .await;
+        let actions: Vec<&str> = history["data"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["create", "vote"]);
+
+        let stats: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/v1/feedbacks/stats")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(stats["count"], 1);
+        assert_eq!(stats["average"], 4.0);
+    }
+
+    #[actix_web::test]
+    async fn replies_are_audited_and_published() {
+        let data = app_state();
+        let mut events = data.events.subscribe();
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .configure(|conf| config(conf, "/api")),
+        )
+        .await;
+
+        let feedback = data
+            .repo
+            .create(
+                &serde_json::from_value(json!({ "text": "Needs dark mode" })).unwrap(),
+                3,
+                "user-1",
+            )
+            .await
+            .unwrap();
+
+        let created = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!("/api/v1/feedbacks/{}/replies", feedback.id))
+                .insert_header((header::AUTHORIZATION, bearer_token()))
+                .set_json(json!({ "text": "On the roadmap" }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(created.status(), StatusCode::CREATED);
+
+        let Ok(FeedbackEvent::Replied(reply)) = events.try_recv() else {
+            panic!("expected a replied event");
+        };
+        assert_eq!(reply.feedback_id, feedback.id);
+        assert_eq!(reply.text, "On the roadmap");
+
+        let history = data.repo.history(feedback.id).await.unwrap();
+        let last = history.last().unwrap();
+        assert_eq!(
+            (last.action.as_str(), last.actor.as_str()),
+            ("reply", "user-1")
+        );
+
+        let replies: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/v1/feedbacks/{}/replies", feedback.id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(replies["data"]["items"][0]["text"], "On the roadmap");
+    }
+
+    #[actix_web::test]
+    async fn replying_to_missing_feedback_is_not_found() {
+        let data = app_state();
+        let app = test::init_service(
+            App::new()
+                .app_data(data.clone())
+                .configure(|conf| config(conf, "/api")),
+        )
+        .await;
+
+        let response = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!(
+                    "/api/v1/feedbacks/{}/replies",
+                    uuid::Uuid::new_v4()
+                ))
+                .insert_header((header::AUTHORIZATION, bearer_token()))
+                .set_json(json!({ "text": "Hello?" }))
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn openapi_servers_follow_the_configured_prefix() {
+        let app = test::init_service(
+            App::new()
+                .app_data(app_state())
+                .configure(|conf| config(conf, "/gateway")),
+        )
+        .await;
+
+        let doc: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri("/gateway/docs/openapi.json")
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(doc["servers"][0]["url"], "/gateway/v1");
+        assert!(doc["paths"]["/feedbacks/{id}"].is_object());
+    }
 }
@@ -0,0 +1,154 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, Header, HeaderValue},
+    Error, HttpMessage,
+};
+
+use crate::error::ApiError;
+
+/// Languages error messages are available in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale {
+    En,
+    Es,
+}
+
+impl Locale {
+    fn from_primary_language(language: &str) -> Option<Self> {
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// The best supported match for the request's `Accept-Language`, or
+    /// English when nothing it lists is supported.
+    pub fn from_request(req: &impl HttpMessage) -> Self {
+        header::AcceptLanguage::parse(req)
+            .ok()
+            .and_then(|accept| {
+                accept.ranked().iter().find_map(|preference| {
+                    preference
+                        .item()
+                        .and_then(|tag| Locale::from_primary_language(tag.primary_language()))
+                })
+            })
+            .unwrap_or(Locale::En)
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+}
+
+/// `error`'s message in `locale`, or `None` to leave it in English.
+///
+/// Translations are built from the variant and its parameters only, so
+/// variants carrying English prose, like `Validation`, stay untranslated
+/// rather than mixing languages.
+pub fn localize(error: &ApiError, locale: Locale) -> Option<String> {
+    match locale {
+        Locale::En => None,
+        Locale::Es => spanish(error),
+    }
+}
+
+fn spanish(error: &ApiError) -> Option<String> {
+    let message = match error {
+        ApiError::FeedbackNotFound(id) => format!("No se encontró la opinión con ID: {}", id),
+        ApiError::Duplicate(_) => "Ya existe una opinión con ese texto".to_string(),
+        ApiError::ProbableDuplicate(id) => format!("La opinión parece un duplicado de {}", id),
+        ApiError::InvalidFields(errors) => {
+            let mut fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+            fields.dedup();
+
+            format!("Algunos campos no son válidos: {}", fields.join(", "))
+        }
+        ApiError::TooManyRequests(retry_after) => format!(
+            "Demasiadas solicitudes, inténtalo de nuevo en {} segundos",
+            retry_after
+        ),
+        ApiError::Timeout(budget) => {
+            format!("La solicitud no terminó en {} ms", budget.as_millis())
+        }
+        _ => return None,
+    };
+
+    Some(message)
+}
+
+/// Re-renders `ApiError` responses in the language the client asked for.
+pub struct Localize;
+
+impl<S, B> Transform<S, ServiceRequest> for Localize
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = LocalizeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizeMiddleware { service }))
+    }
+}
+
+pub struct LocalizeMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let locale = Locale::from_request(&req);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            let Some((error, message)) = res
+                .response()
+                .error()
+                .and_then(|error| error.as_error::<ApiError>())
+                .and_then(|error| Some((error, localize(error, locale)?)))
+            else {
+                return Ok(res.map_into_left_body());
+            };
+
+            let mut localized = error.response_with_message(message);
+            localized.headers_mut().insert(
+                header::CONTENT_LANGUAGE,
+                HeaderValue::from_static(locale.as_str()),
+            );
+            localized
+                .headers_mut()
+                .append(header::VARY, HeaderValue::from_static("Accept-Language"));
+
+            Ok(res.into_response(localized).map_into_right_body())
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{error::ApiError, model::FeedbackModel};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// A key as one user sent it; keys from different users never collide.
+type ScopedKey = (String, String);
+
+struct Entry {
+    /// Body of the request that claimed the key.
+    request: serde_json::Value,
+    /// `None` while that request is still being handled.
+    feedback: Option<FeedbackModel>,
+    stored_at: Instant,
+}
+
+/// Remembers the feedback created for each `Idempotency-Key` so retried
+/// requests get the original result instead of inserting a duplicate.
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<ScopedKey, Entry>>,
+}
+
+/// What a request carrying an `Idempotency-Key` should do.
+pub enum Claim<'a> {
+    /// First use of the key: create the feedback, then `complete` this.
+    New(Reservation<'a>),
+    /// A retry of a request that already succeeded, answered with its result.
+    Replay(FeedbackModel),
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<ScopedKey, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Claims `key` for `user`'s request with body `request`, atomically, so
+    /// concurrent retries cannot both create. Fails with a 409 while another
+    /// request holds the key, or if the key was used with a different body.
+    pub fn claim(
+        &self,
+        user: &str,
+        key: &str,
+        request: serde_json::Value,
+    ) -> Result<Claim<'_>, ApiError> {
+        let key = (user.to_string(), key.to_string());
+        let mut entries = self.entries();
+
+        entries.retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+
+        match entries.get(&key) {
+            Some(entry) if entry.request != request => Err(ApiError::Conflict(
+                "Idempotency-Key was already used with a different request body".to_string(),
+            )),
+            Some(Entry {
+                feedback: Some(feedback),
+                ..
+            }) => Ok(Claim::Replay(feedback.clone())),
+            Some(_) => Err(ApiError::Conflict(
+                "A request with this Idempotency-Key is still in progress".to_string(),
+            )),
+            None => {
+                entries.insert(
+                    key.clone(),
+                    Entry {
+                        request,
+                        feedback: None,
+                        stored_at: Instant::now(),
+                    },
+                );
+
+                Ok(Claim::New(Reservation {
+                    cache: self,
+                    key: Some(key),
+                }))
+            }
+        }
+    }
+}
+
+/// A key claimed by the request being handled. Dropping it without calling
+/// `complete`, e.g. because creating failed or timed out, frees the key for a
+/// retry.
+pub struct Reservation<'a> {
+    cache: &'a IdempotencyCache,
+    key: Option<ScopedKey>,
+}
+
+impl Reservation<'_> {
+    /// Stores `feedback` as the result retries with the key get.
+    pub fn complete(mut self, feedback: FeedbackModel) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+
+        if let Some(entry) = self.cache.entries().get_mut(&key) {
+            entry.feedback = Some(feedback);
+            entry.stored_at = Instant::now();
+        }
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.cache.entries().remove(&key);
+        }
+    }
+}
@@ -1,33 +1,247 @@
 use actix_cors::Cors;
-use actix_web::middleware::Logger;
-use actix_web::{http::header, web, App, HttpServer};
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use actix_web::{http::header, middleware::Compress, rt, web, App, HttpServer};
+use common::FeedbackEvent;
+use db::{Database, PoolSettings, RetryPolicy};
+use features::FeatureFlags;
+use i18n::Localize;
+use idempotency::IdempotencyCache;
+use metrics::{Metrics, RequestMetrics};
+use rate_limit::RateLimiter;
+#[cfg(feature = "sqlite")]
+use repository::SqliteFeedbackRepository;
+use repository::{FeedbackRepository, PgFeedbackRepository, SingleFlightRepository};
+use request_id::RequestTracing;
+use schema::{
+    DEFAULT_ANOMALY_MULTIPLIER, DEFAULT_DEDUPE_THRESHOLD, DEFAULT_MAX_BODY_SIZE,
+    DEFAULT_MAX_TEXT_LENGTH, DEFAULT_RATING, MAX_RATING, MIN_RATING,
+};
+use stats_cache::StatsCache;
+use std::{sync::Arc, time::Duration};
+use timeout::RouteTimeouts;
+use tokio::sync::broadcast;
+use tracing_subscriber::EnvFilter;
+use webhook::Webhook;
 
+mod audit;
+mod auth;
+mod db;
+mod error;
+mod features;
+mod filter;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod handler;
+mod i18n;
+mod idempotency;
+mod metrics;
 mod model;
+mod negotiate;
+mod openapi;
+mod rate_limit;
+mod repository;
+mod request_id;
+mod retention;
 mod schema;
+mod seed;
+mod shutdown;
+mod single_flight;
+mod sse;
+mod stats_cache;
+mod timeout;
+mod timezone;
+mod webhook;
+mod ws;
 
 pub struct AppState {
-    db: Pool<Postgres>,
+    repo: Arc<dyn FeedbackRepository>,
+    jwt_secret: String,
+    events: broadcast::Sender<FeedbackEvent>,
+    create_limiter: RateLimiter,
+    idempotency: IdempotencyCache,
+    stats_cache: StatsCache,
+    webhook: Option<Webhook>,
+    max_page_size: usize,
+    max_text_length: usize,
+    default_rating: i32,
+    dedupe_threshold: f32,
+    anomaly_multiplier: f64,
+    /// Token `DELETE /feedbacks/all` must be confirmed with; `None` leaves the
+    /// endpoint disabled even when destructive endpoints are allowed.
+    wipe_confirm_token: Option<String>,
+    features: FeatureFlags,
+    metrics: Metrics,
+    route_timeouts: RouteTimeouts,
+    api_scope: String,
+}
+
+impl AppState {
+    /// Tells subscribers, and the webhook for creates, about a change and
+    /// drops anything cached from before it.
+    fn publish(&self, event: FeedbackEvent) {
+        self.stats_cache.invalidate();
+
+        if let (Some(webhook), FeedbackEvent::Created(feedback)) = (&self.webhook, &event) {
+            webhook.dispatch(feedback.clone());
+        }
+
+        let _ = self.events.send(event);
+    }
+}
+
+fn allowed_origins() -> Vec<String> {
+    std::env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_else(|_| "http://localhost:3000".to_string())
+        .split(',')
+        .map(|origin| origin.trim().to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect()
+}
+
+fn api_prefix() -> String {
+    let prefix = std::env::var("API_PREFIX").unwrap_or_else(|_| "/api".to_string());
+    let prefix = prefix.trim().trim_matches('/');
+
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", prefix)
+    }
+}
+
+/// Where the HTTP server listens and how many workers it runs.
+struct ServerSettings {
+    host: String,
+    port: u16,
+    /// `None` leaves actix's default of one worker per physical core.
+    workers: Option<usize>,
+}
+
+impl ServerSettings {
+    fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Reads `HOST`, `PORT` and `WORKERS` through `var`, falling back to the
+    /// defaults for anything unset or unparseable.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let host = var("HOST")
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
+            .unwrap_or_else(|| "127.0.0.1".to_string());
+        let port = var("PORT")
+            .and_then(|port| port.trim().parse().ok())
+            .unwrap_or(8000);
+        let workers = var("WORKERS")
+            .and_then(|workers| workers.trim().parse().ok())
+            .filter(|&workers: &usize| workers > 0);
+
+        Self {
+            host,
+            port,
+            workers,
+        }
+    }
+}
+
+fn cors(allowed_origins: &[String]) -> Cors {
+    allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"])
+        .allowed_headers(vec![
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::ACCEPT,
+            header::HeaderName::from_static(idempotency::IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers(vec![header::LINK.as_str(), handler::TOTAL_COUNT_HEADER])
+        .supports_credentials()
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     if std::env::var_os("RUST_LOG").is_none() {
-        std::env::set_var("RUST_LOG", "actix_web=info");
+        std::env::set_var("RUST_LOG", "actix_web=info,backend=info,sqlx=warn");
     }
     dotenv::dotenv().unwrap();
-    env_logger::init();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
 
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let pool = match PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
-        .await
-    {
-        Ok(pool) => {
+    let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    let allowed_origins = allowed_origins();
+    let server_settings = ServerSettings::from_env();
+    let api_prefix = api_prefix();
+    let create_rate_limit = std::env::var("CREATE_RATE_LIMIT_PER_MINUTE")
+        .ok()
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(10);
+    let idempotency_ttl = std::env::var("IDEMPOTENCY_KEY_TTL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(24 * 60 * 60);
+    let max_page_size = std::env::var("MAX_PAGE_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(100usize)
+        .max(1);
+    let max_text_length = std::env::var("MAX_TEXT_LENGTH")
+        .ok()
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(DEFAULT_MAX_TEXT_LENGTH);
+    let default_rating = std::env::var("DEFAULT_RATING")
+        .ok()
+        .and_then(|rating| rating.parse().ok())
+        .unwrap_or(DEFAULT_RATING)
+        .clamp(MIN_RATING, MAX_RATING);
+    let dedupe_threshold = std::env::var("DEDUPE_SIMILARITY_THRESHOLD")
+        .ok()
+        .and_then(|threshold| threshold.parse().ok())
+        .unwrap_or(DEFAULT_DEDUPE_THRESHOLD);
+    let max_body_size = std::env::var("MAX_BODY_SIZE_BYTES")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_MAX_BODY_SIZE);
+    let anomaly_multiplier = std::env::var("ANOMALY_SPIKE_MULTIPLIER")
+        .ok()
+        .and_then(|multiplier| multiplier.parse().ok())
+        .unwrap_or(DEFAULT_ANOMALY_MULTIPLIER);
+    let features = FeatureFlags::from_env();
+    let wipe_confirm_token = std::env::var("WIPE_CONFIRM_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+    if features.allow_destructive && wipe_confirm_token.is_none() {
+        println!("⚠️ ALLOW_DESTRUCTIVE is set without WIPE_CONFIRM_TOKEN, DELETE /feedbacks/all stays disabled");
+    }
+    let stats_cache_ttl = std::env::var("STATS_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(5);
+    let retention_days = std::env::var("RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .filter(|&days: &i32| days > 0);
+    let retention_interval = std::env::var("RETENTION_INTERVAL_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .filter(|&secs: &u64| secs > 0)
+        .unwrap_or(24 * 60 * 60);
+    let shutdown_timeout = std::env::var("SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(30);
+    if features.allow_duplicate_text {
+        println!("👯 ALLOW_DUPLICATE_TEXT is set, feedback text is not required to be unique");
+    }
+    let pool_settings = PoolSettings {
+        unique_text: !features.allow_duplicate_text,
+        ..PoolSettings::from_env()
+    };
+    let database = match pool_settings.connect(&database_url).await {
+        Ok(database) => {
             println!("✅Connection to the database is successful!");
-            pool
+            database
         }
         Err(e) => {
             println!("🔥 Failed to connect to the database: {:?}", e);
@@ -35,26 +249,151 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
-    println!("🚀 Server started successfully");
+    let skip_migrations = std::env::var("SKIP_MIGRATIONS")
+        .map(|skip| matches!(skip.trim(), "1" | "true"))
+        .unwrap_or(false);
+
+    if skip_migrations {
+        println!("⏭️ SKIP_MIGRATIONS is set, not running migrations");
+    } else {
+        match database.run_migrations().await {
+            Ok(applied) if applied.is_empty() => println!("✅ Database schema is up to date"),
+            Ok(applied) => {
+                for migration in applied {
+                    println!(
+                        "📦 Applied migration {} {}",
+                        migration.version, migration.description
+                    );
+                }
+            }
+            Err(e) => {
+                println!("🔥 Failed to run database migrations: {:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if std::env::args().any(|arg| arg == "--seed") {
+        match seed::seed(&database).await {
+            Ok(0) => println!("🌱 Feedback table is not empty, skipping seed"),
+            Ok(count) => println!("🌱 Seeded {} feedback", count),
+            Err(e) => {
+                println!("🔥 Failed to seed the database: {:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let retry = RetryPolicy::from_env();
+    let repo: Arc<dyn FeedbackRepository> = match &database {
+        Database::Postgres(pool) => Arc::new(SingleFlightRepository::new(
+            PgFeedbackRepository::new(pool.clone(), retry),
+        )),
+        #[cfg(feature = "sqlite")]
+        Database::Sqlite { pool, .. } => Arc::new(SingleFlightRepository::new(
+            SqliteFeedbackRepository::new(pool.clone(), retry),
+        )),
+    };
+
+    if let Some(retention_days) = retention_days {
+        println!(
+            "🧹 Purging feedback older than {} days every {} seconds",
+            retention_days, retention_interval
+        );
+        retention::spawn_cleanup_task(
+            repo.clone(),
+            Duration::from_secs(retention_interval),
+            retention_days,
+        );
+    }
+
+    let (events, _) = broadcast::channel(100);
+    let webhook = Webhook::from_env();
+    if let Some(webhook) = &webhook {
+        println!("🪝 Posting new feedback to {}", webhook.url());
+    }
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allowed_origin("http://localhost:3000")
-            .allowed_methods(vec!["GET", "POST", "PATCH", "DELETE"])
-            .allowed_headers(vec![
-                header::CONTENT_TYPE,
-                header::AUTHORIZATION,
-                header::ACCEPT,
-            ])
-            .supports_credentials();
+    let app_state = web::Data::new(AppState {
+        repo,
+        jwt_secret,
+        events,
+        create_limiter: RateLimiter::per_minute(create_rate_limit),
+        idempotency: IdempotencyCache::new(Duration::from_secs(idempotency_ttl)),
+        stats_cache: StatsCache::new(Duration::from_secs(stats_cache_ttl)),
+        webhook,
+        max_page_size,
+        max_text_length,
+        default_rating,
+        dedupe_threshold,
+        anomaly_multiplier,
+        wipe_confirm_token,
+        features,
+        metrics: Metrics::new(),
+        route_timeouts: RouteTimeouts::from_env(),
+        api_scope: handler::api_scope(&api_prefix),
+    });
+
+    let server = HttpServer::new(move || {
+        let cors = cors(&allowed_origins);
 
         App::new()
-            .app_data(web::Data::new(AppState { db: pool.clone() }))
-            .configure(handler::config)
+            .app_data(app_state.clone())
+            .app_data(web::PayloadConfig::new(max_body_size))
+            .app_data(web::PathConfig::default().error_handler(error::path_error_handler))
+            .app_data(
+                web::JsonConfig::default()
+                    .limit(max_body_size)
+                    .error_handler(error::json_error_handler),
+            )
+            .configure(|conf| handler::config(conf, &api_prefix))
+            .wrap(Localize)
+            .wrap(Compress::default())
             .wrap(cors)
-            .wrap(Logger::default())
+            .wrap(RequestMetrics)
+            .wrap(RequestTracing)
     })
-    .bind(("127.0.0.1", 8000))?
-    .run()
-    .await
+    .disable_signals()
+    .shutdown_timeout(shutdown_timeout);
+
+    let server = match server_settings.workers {
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+
+    let address = (server_settings.host.as_str(), server_settings.port);
+    let server = match server.bind(address) {
+        Ok(server) => server,
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+            println!(
+                "🔥 {}:{} is already in use, set HOST or PORT to listen elsewhere",
+                server_settings.host, server_settings.port
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            println!(
+                "🔥 Failed to bind {}:{}: {:?}",
+                server_settings.host, server_settings.port, e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    for addr in server.addrs() {
+        println!("🚀 Server started successfully on http://{}", addr);
+    }
+
+    let server = server.run();
+
+    let handle = server.handle();
+    rt::spawn(async move {
+        let signal = shutdown::wait_for_signal().await;
+        println!("🛑 Received {}, draining in-flight requests", signal);
+        handle.stop(true).await;
+    });
+
+    server.await?;
+    shutdown::close_pool(&database).await;
+
+    Ok(())
 }
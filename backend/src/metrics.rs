@@ -0,0 +1,162 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    time::Instant,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    get, web, Error, HttpResponse,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+use crate::AppState;
+
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    db_connections: IntGauge,
+    db_idle_connections: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "path", "status"],
+        )
+        .expect("valid metric");
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "path"],
+        )
+        .expect("valid metric");
+        let db_connections = IntGauge::new("db_pool_connections", "Open database connections")
+            .expect("valid metric");
+        let db_idle_connections =
+            IntGauge::new("db_pool_idle_connections", "Idle database connections")
+                .expect("valid metric");
+
+        registry
+            .register(Box::new(requests.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(latency.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_connections.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_idle_connections.clone()))
+            .expect("metric registered once");
+
+        Self {
+            registry,
+            requests,
+            latency,
+            db_connections,
+            db_idle_connections,
+        }
+    }
+
+    fn observe(&self, method: &str, path: &str, status: u16, seconds: f64) {
+        self.requests
+            .with_label_values(&[method, path, &status.to_string()])
+            .inc();
+        self.latency
+            .with_label_values(&[method, path])
+            .observe(seconds);
+    }
+}
+
+#[get("/metrics")]
+pub async fn metrics_handler(data: web::Data<AppState>) -> HttpResponse {
+    if let Some(pool) = data.repo.pool_status() {
+        data.metrics.db_connections.set(pool.size as i64);
+        data.metrics.db_idle_connections.set(pool.idle as i64);
+    }
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&data.metrics.registry.gather(), &mut buffer) {
+        return HttpResponse::InternalServerError().body(e.to_string());
+    }
+
+    HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(buffer)
+}
+
+/// Records a request counter and latency sample per route pattern, so that
+/// `/api/v1/feedbacks/{id}` is one series rather than one per id.
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware { service }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let data = req.app_data::<web::Data<AppState>>().cloned();
+        let method = req.method().to_string();
+        let start = Instant::now();
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            if let Some(data) = data {
+                let path = res
+                    .request()
+                    .match_pattern()
+                    .unwrap_or_else(|| "unmatched".to_string());
+
+                data.metrics.observe(
+                    &method,
+                    &path,
+                    res.status().as_u16(),
+                    start.elapsed().as_secs_f64(),
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
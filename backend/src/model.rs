@@ -1,15 +1,134 @@
 use chrono::{DateTime, Utc};
+use common::{Feedback, FeedbackReply};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::FromRow;
 
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
 #[allow(non_snake_case)]
 pub struct FeedbackModel {
     pub id: uuid::Uuid,
     pub text: String,
     pub rating: i32,
+    pub category: Option<String>,
+    #[serde(rename = "authorEmail", skip_serializing_if = "Option::is_none")]
+    pub author_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    pub upvotes: i32,
+    pub downvotes: i32,
     #[serde(rename = "createdAt")]
-    pub created_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
     #[serde(rename = "updatedAt")]
-    pub updated_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// JSON field names a client may pick with `?fields=`.
+pub const FEEDBACK_FIELDS: &[&str] = &[
+    "id",
+    "text",
+    "rating",
+    "category",
+    "authorEmail",
+    "metadata",
+    "upvotes",
+    "downvotes",
+    "createdAt",
+    "updatedAt",
+    "deletedAt",
+];
+
+impl FeedbackModel {
+    /// The feedback as JSON with `text` cut to at most `max_chars` characters
+    /// plus an ellipsis, and a `truncated` flag saying whether it was cut.
+    pub fn preview(&self, max_chars: usize) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        let cut = self.text.char_indices().nth(max_chars).map(|(at, _)| at);
+
+        if let Some(object) = value.as_object_mut() {
+            if let Some(at) = cut {
+                object.insert("text".to_string(), format!("{}…", &self.text[..at]).into());
+            }
+            object.insert("truncated".to_string(), cut.is_some().into());
+        }
+
+        value
+    }
+
+    /// The feedback as JSON with only `fields` kept.
+    pub fn project(&self, fields: &[&str]) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+
+        if let Some(object) = value.as_object_mut() {
+            object.retain(|name, _| fields.contains(&name.as_str()));
+        }
+
+        value
+    }
+
+    /// Opaque tag that changes whenever the feedback's content or `updated_at`
+    /// does. A SHA-256 of its JSON, so it stays the same across builds and
+    /// restarts.
+    pub fn etag(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+
+        format!("{:x}", Sha256::digest(json))
+    }
+}
+
+impl From<FeedbackModel> for Feedback {
+    fn from(feedback: FeedbackModel) -> Self {
+        Feedback {
+            id: feedback.id,
+            text: feedback.text,
+            rating: feedback.rating as u8,
+            category: feedback.category,
+            author_email: feedback.author_email,
+            metadata: feedback.metadata,
+            upvotes: feedback.upvotes,
+            downvotes: feedback.downvotes,
+            created_at: feedback.created_at,
+            updated_at: feedback.updated_at,
+            deleted_at: feedback.deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FeedbackReplyModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "feedbackId")]
+    pub feedback_id: uuid::Uuid,
+    pub text: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<FeedbackReplyModel> for FeedbackReply {
+    fn from(reply: FeedbackReplyModel) -> Self {
+        FeedbackReply {
+            id: reply.id,
+            feedback_id: reply.feedback_id,
+            text: reply.text,
+            created_at: reply.created_at,
+        }
+    }
+}
+
+/// One row of the audit trail; `before`/`after` are the feedback as it was
+/// serialized on either side of the change, absent on create and so on.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AuditEntryModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "feedbackId")]
+    pub feedback_id: uuid::Uuid,
+    pub action: String,
+    pub actor: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
 }
@@ -0,0 +1,78 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    dev::Payload,
+    http::header::{self, Header},
+    FromRequest, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use serde::Serialize;
+
+use crate::error::ApiError;
+
+/// Root element XML responses are wrapped in.
+const XML_ROOT: &str = "response";
+
+/// Body format picked from the request's `Accept` header. A missing header or
+/// a wildcard gets JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseFormat {
+    Json,
+    Xml,
+}
+
+impl ResponseFormat {
+    fn from_mime(mime: &mime::Mime) -> Option<Self> {
+        match (mime.type_(), mime.subtype()) {
+            (mime::STAR, mime::STAR) | (mime::APPLICATION, mime::STAR | mime::JSON) => {
+                Some(ResponseFormat::Json)
+            }
+            (mime::APPLICATION | mime::TEXT, mime::XML) => Some(ResponseFormat::Xml),
+            _ => None,
+        }
+    }
+
+    /// Finishes `response` with `body` in this format.
+    pub fn respond<T: Serialize>(
+        &self,
+        mut response: HttpResponseBuilder,
+        body: &T,
+    ) -> Result<HttpResponse, ApiError> {
+        response.insert_header((header::VARY, "Accept"));
+
+        match self {
+            ResponseFormat::Json => Ok(response.json(body)),
+            ResponseFormat::Xml => {
+                // Only a bug can make a body unserializable, so this is ours to
+                // fix rather than the client's to negotiate around.
+                let xml = quick_xml::se::to_string_with_root(XML_ROOT, body).map_err(|e| {
+                    tracing::error!(error = %e, "failed to serialize response as XML");
+                    ApiError::Internal("Response could not be rendered as XML".to_string())
+                })?;
+
+                Ok(response.content_type("application/xml").body(xml))
+            }
+        }
+    }
+}
+
+impl FromRequest for ResponseFormat {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if !req.headers().contains_key(header::ACCEPT) {
+            return ready(Ok(ResponseFormat::Json));
+        }
+
+        let format = header::Accept::parse(req)
+            .ok()
+            .and_then(|accept| accept.ranked().iter().find_map(ResponseFormat::from_mime))
+            .ok_or_else(|| {
+                ApiError::NotAcceptable(
+                    "Accept must allow application/json or application/xml".to_string(),
+                )
+            });
+
+        ready(format)
+    }
+}
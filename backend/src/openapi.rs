@@ -0,0 +1,139 @@
+use common::{
+    AuditEntry, AuditEntryPage, ErrorResponse, Feedback, FeedbackAnomalyResponse,
+    FeedbackBatchDeleteResponse, FeedbackBulkUpdateResponse, FeedbackCategoryCount,
+    FeedbackCategoryResponse, FeedbackCountResponse, FeedbackData, FeedbackHistoryResponse,
+    FeedbackImportResponse, FeedbackListResponse, FeedbackPage, FeedbackReply, FeedbackReplyData,
+    FeedbackReplyListResponse, FeedbackReplyPage, FeedbackReplyResponse, FeedbackResponse,
+    FeedbackSimilarResponse, FeedbackStatsResponse, FeedbackTrendPoint, FeedbackTrendResponse,
+    FeedbackWipeResponse, FeedbackWithReplyData, FeedbackWithReplyResponse, FieldError,
+    ImportRowError, ResponseStatus, Sentiment, SimilarFeedback, SimilarFeedbackPage,
+    ValidationErrorResponse,
+};
+use utoipa::{
+    openapi::{
+        security::{Http, HttpAuthScheme, SecurityScheme},
+        Server,
+    },
+    Modify, OpenApi,
+};
+
+use crate::{
+    handler,
+    schema::{
+        BatchDeleteSchema, BulkRatingSchema, CreateFeedbackSchema, CreateFeedbackWithReplySchema,
+        CreateReplySchema, FetchByIdsSchema, UpdateFeedbackSchema, VoteSchema,
+    },
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handler::health_checker_handler,
+        handler::liveness_handler,
+        handler::readiness_handler,
+        handler::feedback_list_handler,
+        handler::feedback_head_handler,
+        handler::feedback_count_handler,
+        handler::feedback_stats_handler,
+        handler::recent_feedback_handler,
+        handler::worst_feedback_handler,
+        handler::best_feedback_handler,
+        handler::feedback_trend_handler,
+        handler::feedback_by_category_handler,
+        handler::feedback_anomalies_handler,
+        handler::create_feedback_handler,
+        handler::create_feedback_with_reply_handler,
+        handler::create_feedback_batch_handler,
+        handler::fetch_feedback_by_ids_handler,
+        handler::delete_feedback_batch_handler,
+        handler::delete_all_feedback_handler,
+        handler::bulk_rating_handler,
+        handler::export_feedback_csv_handler,
+        handler::import_feedback_csv_handler,
+        handler::stream_feedback_handler,
+        handler::get_feedback_handler,
+        handler::replace_feedback_handler,
+        handler::edit_feedback_handler,
+        handler::delete_feedback_handler,
+        handler::restore_feedback_handler,
+        handler::anonymize_feedback_handler,
+        handler::vote_feedback_handler,
+        handler::touch_feedback_handler,
+        handler::create_reply_handler,
+        handler::reply_list_handler,
+        handler::similar_feedback_handler,
+        handler::feedback_history_handler,
+    ),
+    components(schemas(
+        Feedback,
+        FeedbackData,
+        FeedbackResponse,
+        FeedbackListResponse,
+        FeedbackPage,
+        SimilarFeedbackPage,
+        FeedbackReplyPage,
+        AuditEntryPage,
+        FeedbackStatsResponse,
+        FeedbackTrendPoint,
+        FeedbackTrendResponse,
+        FeedbackCategoryCount,
+        FeedbackCategoryResponse,
+        FeedbackAnomalyResponse,
+        FeedbackCountResponse,
+        FeedbackBatchDeleteResponse,
+        FeedbackWipeResponse,
+        ImportRowError,
+        FeedbackImportResponse,
+        FeedbackBulkUpdateResponse,
+        FeedbackReply,
+        FeedbackReplyData,
+        FeedbackReplyResponse,
+        FeedbackWithReplyData,
+        FeedbackWithReplyResponse,
+        FeedbackReplyListResponse,
+        SimilarFeedback,
+        FeedbackSimilarResponse,
+        AuditEntry,
+        FeedbackHistoryResponse,
+        ResponseStatus,
+        ErrorResponse,
+        FieldError,
+        ValidationErrorResponse,
+        Sentiment,
+        CreateFeedbackSchema,
+        BatchDeleteSchema,
+        BulkRatingSchema,
+        FetchByIdsSchema,
+        UpdateFeedbackSchema,
+        CreateReplySchema,
+        CreateFeedbackWithReplySchema,
+        VoteSchema,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "feedback", description = "Feedback management endpoints"),
+        (name = "health", description = "Service health endpoints")
+    )
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI document served under `scope`, the configured prefix and
+/// version, which is advertised as the server the paths are relative to.
+pub fn api_doc(scope: &str) -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    doc.servers = Some(vec![Server::new(scope)]);
+    doc
+}
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
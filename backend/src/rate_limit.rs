@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Ready},
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+
+use crate::{error::ApiError, AppState};
+
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(limit: u32) -> Self {
+        let limit = limit.max(1);
+
+        Self {
+            capacity: limit as f64,
+            refill_per_sec: limit as f64 / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes one token for `client`, or returns how long until one is available.
+    pub fn check(&self, client: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            let full_after = Duration::from_secs_f64(self.capacity / self.refill_per_sec);
+            buckets.retain(|_, bucket| now.duration_since(bucket.last_refill) < full_after);
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// Extractor that rejects the request with a 429 once the client has used up
+/// its create budget.
+pub struct CreateRateLimit;
+
+impl FromRequest for CreateRateLimit {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let data = req
+            .app_data::<web::Data<AppState>>()
+            .expect("AppState must be registered as app data");
+
+        let Some(client) = req.peer_addr().map(|addr| addr.ip()) else {
+            return ready(Ok(CreateRateLimit));
+        };
+
+        ready(
+            data.create_limiter
+                .check(client)
+                .map(|_| CreateRateLimit)
+                .map_err(|retry_after| {
+                    ApiError::TooManyRequests(retry_after.as_secs_f64().ceil() as u64)
+                }),
+        )
+    }
+}
@@ -0,0 +1,621 @@
+use async_trait::async_trait;
+use common::{FeedbackCategoryCount, FeedbackTrendPoint};
+use futures_util::stream::BoxStream;
+
+use crate::{
+    error::ApiError,
+    filter::ValidatedFilter,
+    model::{AuditEntryModel, FeedbackModel, FeedbackReplyModel},
+    schema::{CreateFeedbackSchema, FilterOptions, TrendBucket, UpdateFeedbackSchema},
+    single_flight::SingleFlight,
+};
+
+#[cfg(test)]
+mod memory;
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(any(test, feature = "sqlite"))]
+mod trigram;
+
+#[cfg(test)]
+pub use memory::InMemoryFeedbackRepository;
+pub use postgres::PgFeedbackRepository;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteFeedbackRepository;
+
+/// Storage for feedback and everything hanging off it (replies, the audit
+/// log), so handlers never touch a database pool.
+///
+/// Every write takes the `actor` making it and records it in the audit log in
+/// the same transaction as the change.
+#[async_trait]
+pub trait FeedbackRepository: Send + Sync {
+    /// Fails unless the storage can serve a query right now.
+    async fn ping(&self) -> Result<(), ApiError>;
+
+    /// Like `ping`, but also requires the schema to be fully migrated, and
+    /// says what is wrong otherwise.
+    async fn check_ready(&self) -> Result<(), String>;
+
+    /// Connection counts for the metrics endpoint, when there is a pool.
+    fn pool_status(&self) -> Option<PoolStatus>;
+
+    async fn list(&self, filter: &ValidatedFilter) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    async fn count(&self, filter: &FilterOptions) -> Result<i64, ApiError>;
+
+    /// The feedback with `id`, unless it is missing or soft-deleted.
+    async fn get(&self, id: uuid::Uuid) -> Result<Option<FeedbackModel>, ApiError>;
+
+    /// The live feedback among `ids`, in the order the ids were given; ids that
+    /// match nothing are skipped.
+    async fn get_many(&self, ids: &[uuid::Uuid]) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    /// Live feedback updated in the last `days` days, newest first.
+    async fn recent(
+        &self,
+        days: i32,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    async fn count_recent(&self, days: i32) -> Result<i64, ApiError>;
+
+    /// Live feedback by rating in `order`, newest first within a rating.
+    async fn ranked(
+        &self,
+        order: RatingOrder,
+        limit: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    /// How many live feedback have each rating; ratings nobody gave are left
+    /// out.
+    async fn rating_counts(&self) -> Result<Vec<(i32, i64)>, ApiError>;
+
+    /// Live feedback created in the last `days` days, grouped by `bucket`,
+    /// oldest first; empty buckets are left out.
+    async fn trend(
+        &self,
+        bucket: TrendBucket,
+        days: i32,
+    ) -> Result<Vec<FeedbackTrendPoint>, ApiError>;
+
+    /// Live feedback per category, largest first, with feedback without one
+    /// counted under `uncategorized`.
+    async fn category_counts(&self) -> Result<Vec<FeedbackCategoryCount>, ApiError>;
+
+    /// Lowest-rated feedback, deleted or not, in the last `window_mins` and in
+    /// the `baseline_days` before that.
+    async fn low_rating_counts(
+        &self,
+        window_mins: i32,
+        baseline_days: i32,
+    ) -> Result<WindowCounts, ApiError>;
+
+    /// The live feedback whose text matches `text` ignoring case.
+    async fn find_by_text(&self, text: &str) -> Result<Option<uuid::Uuid>, ApiError>;
+
+    /// The most similar live feedback whose trigram similarity to `text` is
+    /// above `threshold`, if any.
+    async fn most_similar(
+        &self,
+        text: &str,
+        threshold: f32,
+    ) -> Result<Option<uuid::Uuid>, ApiError>;
+
+    /// Other live feedback with text in common with `feedback` and how
+    /// similar each is, most similar first.
+    async fn similar_to(
+        &self,
+        feedback: &FeedbackModel,
+        limit: usize,
+    ) -> Result<Vec<(FeedbackModel, f32)>, ApiError>;
+
+    /// Every live feedback, oldest first, read as the stream is polled.
+    fn stream_live(&self) -> BoxStream<'_, Result<FeedbackModel, ApiError>>;
+
+    /// The audit trail of `id`, oldest first. Soft-deleted feedback keeps its
+    /// history, so only a missing row is `FeedbackNotFound`.
+    async fn history(&self, id: uuid::Uuid) -> Result<Vec<AuditEntryModel>, ApiError>;
+
+    /// Replies to the live feedback `feedback_id`, oldest first.
+    async fn replies(&self, feedback_id: uuid::Uuid) -> Result<Vec<FeedbackReplyModel>, ApiError>;
+
+    async fn create(
+        &self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError>;
+
+    /// Creates the feedback and its first reply, storing neither unless both
+    /// are.
+    async fn create_with_reply(
+        &self,
+        body: &CreateFeedbackSchema,
+        reply: &str,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<(FeedbackModel, FeedbackReplyModel), ApiError>;
+
+    /// Creates every feedback in `bodies` or none of them, returned in the
+    /// order of `bodies`.
+    async fn create_batch(
+        &self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    /// Starts an import whose batches are only kept once it is committed.
+    async fn begin_import(
+        &self,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Box<dyn FeedbackImport + '_>, ApiError>;
+
+    /// Overwrites every field, returning `None` if there is nothing to replace.
+    async fn replace(
+        &self,
+        id: uuid::Uuid,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Option<FeedbackModel>, ApiError>;
+
+    /// Applies the fields present in `body`, leaving the rest untouched; an
+    /// empty body returns the feedback as it is without bumping `updated_at`.
+    async fn update(
+        &self,
+        id: uuid::Uuid,
+        body: &UpdateFeedbackSchema,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError>;
+
+    /// Sets the rating of every live feedback in `ids`, returning the ones
+    /// that matched.
+    async fn set_rating(
+        &self,
+        ids: &[uuid::Uuid],
+        rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError>;
+
+    async fn vote(
+        &self,
+        id: uuid::Uuid,
+        upvotes: i32,
+        downvotes: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError>;
+
+    /// Bumps `updated_at` and nothing else.
+    async fn touch(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError>;
+
+    /// Replaces the text with a placeholder and drops the author's email and
+    /// metadata, here and in the audit trail. Works on deleted feedback too.
+    async fn anonymize(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError>;
+
+    async fn restore(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError>;
+
+    async fn delete(&self, id: uuid::Uuid, actor: &str) -> Result<(), ApiError>;
+
+    /// Soft-deletes the live feedback among `ids`, returning the ones that
+    /// matched. With `strict`, deletes nothing and fails with `NotFound` unless
+    /// every id matched.
+    async fn delete_many(
+        &self,
+        ids: &[uuid::Uuid],
+        strict: bool,
+        actor: &str,
+    ) -> Result<Vec<uuid::Uuid>, ApiError>;
+
+    /// Permanently removes every feedback and its replies, returning the ids
+    /// of the feedback removed. The audit log is kept.
+    async fn delete_all(&self) -> Result<Vec<uuid::Uuid>, ApiError>;
+
+    /// Permanently removes feedback created more than `retention_days` ago,
+    /// with its replies, returning how many were removed. Their audit entries
+    /// are kept but scrubbed of personal data.
+    async fn purge_expired(&self, retention_days: i32) -> Result<u64, ApiError>;
+
+    /// Replies to the live feedback `feedback_id`, auditing it as a `reply`
+    /// to the feedback.
+    async fn create_reply(
+        &self,
+        feedback_id: uuid::Uuid,
+        text: &str,
+        actor: &str,
+    ) -> Result<FeedbackReplyModel, ApiError>;
+}
+
+/// An import in progress, see [`FeedbackRepository::begin_import`]. Dropping
+/// it without committing discards everything inserted.
+#[async_trait]
+pub trait FeedbackImport: Send {
+    /// Inserts `bodies`, skipping the ones whose text another live feedback
+    /// already has, including one earlier in the import. Returns the indices
+    /// into `bodies` of the ones skipped.
+    async fn insert(&mut self, bodies: &[CreateFeedbackSchema]) -> Result<Vec<usize>, ApiError>;
+
+    async fn commit(self: Box<Self>) -> Result<(), ApiError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RatingOrder {
+    Lowest,
+    Highest,
+}
+
+/// Feedback counted in a window and in the baseline period before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowCounts {
+    pub current: i64,
+    pub baseline: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStatus {
+    pub size: u32,
+    pub idle: usize,
+}
+
+/// Bucket for feedback filed without a category.
+pub const UNCATEGORIZED: &str = "uncategorized";
+
+/// The ids in `requested` that are not in `found`, sorted and deduplicated.
+pub fn missing_ids(requested: &[uuid::Uuid], found: &[uuid::Uuid]) -> Vec<uuid::Uuid> {
+    let mut missing: Vec<uuid::Uuid> = requested
+        .iter()
+        .filter(|id| !found.contains(id))
+        .copied()
+        .collect();
+    missing.sort();
+    missing.dedup();
+
+    missing
+}
+
+/// The error a strict batch fails with when `ids` matched nothing.
+pub fn not_found_error(ids: &[uuid::Uuid]) -> ApiError {
+    let ids: Vec<String> = ids.iter().map(uuid::Uuid::to_string).collect();
+
+    ApiError::NotFound(format!("Feedback with IDs: {} not found", ids.join(", ")))
+}
+
+/// Wraps a repository so concurrent `get`s for the same id share one query
+/// instead of each hitting the database.
+///
+/// Reads are only coalesced, never cached: a result goes to the callers that
+/// arrived while its query ran. Every write through this repository retires
+/// the queries in flight for the ids it changed, so callers arriving after the
+/// write start a fresh one rather than get what was read before it.
+pub struct SingleFlightRepository<R> {
+    inner: R,
+    gets: SingleFlight<uuid::Uuid, Option<FeedbackModel>>,
+}
+
+impl<R> SingleFlightRepository<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            gets: SingleFlight::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: FeedbackRepository> FeedbackRepository for SingleFlightRepository<R> {
+    async fn ping(&self) -> Result<(), ApiError> {
+        self.inner.ping().await
+    }
+
+    async fn check_ready(&self) -> Result<(), String> {
+        self.inner.check_ready().await
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        self.inner.pool_status()
+    }
+
+    async fn list(&self, filter: &ValidatedFilter) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.inner.list(filter).await
+    }
+
+    async fn count(&self, filter: &FilterOptions) -> Result<i64, ApiError> {
+        self.inner.count(filter).await
+    }
+
+    async fn get(&self, id: uuid::Uuid) -> Result<Option<FeedbackModel>, ApiError> {
+        self.gets.run(id, || self.inner.get(id)).await
+    }
+
+    async fn get_many(&self, ids: &[uuid::Uuid]) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.inner.get_many(ids).await
+    }
+
+    async fn recent(
+        &self,
+        days: i32,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.inner.recent(days, limit, offset).await
+    }
+
+    async fn count_recent(&self, days: i32) -> Result<i64, ApiError> {
+        self.inner.count_recent(days).await
+    }
+
+    async fn ranked(
+        &self,
+        order: RatingOrder,
+        limit: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.inner.ranked(order, limit).await
+    }
+
+    async fn rating_counts(&self) -> Result<Vec<(i32, i64)>, ApiError> {
+        self.inner.rating_counts().await
+    }
+
+    async fn trend(
+        &self,
+        bucket: TrendBucket,
+        days: i32,
+    ) -> Result<Vec<FeedbackTrendPoint>, ApiError> {
+        self.inner.trend(bucket, days).await
+    }
+
+    async fn category_counts(&self) -> Result<Vec<FeedbackCategoryCount>, ApiError> {
+        self.inner.category_counts().await
+    }
+
+    async fn low_rating_counts(
+        &self,
+        window_mins: i32,
+        baseline_days: i32,
+    ) -> Result<WindowCounts, ApiError> {
+        self.inner
+            .low_rating_counts(window_mins, baseline_days)
+            .await
+    }
+
+    async fn find_by_text(&self, text: &str) -> Result<Option<uuid::Uuid>, ApiError> {
+        self.inner.find_by_text(text).await
+    }
+
+    async fn most_similar(
+        &self,
+        text: &str,
+        threshold: f32,
+    ) -> Result<Option<uuid::Uuid>, ApiError> {
+        self.inner.most_similar(text, threshold).await
+    }
+
+    async fn similar_to(
+        &self,
+        feedback: &FeedbackModel,
+        limit: usize,
+    ) -> Result<Vec<(FeedbackModel, f32)>, ApiError> {
+        self.inner.similar_to(feedback, limit).await
+    }
+
+    fn stream_live(&self) -> BoxStream<'_, Result<FeedbackModel, ApiError>> {
+        self.inner.stream_live()
+    }
+
+    async fn history(&self, id: uuid::Uuid) -> Result<Vec<AuditEntryModel>, ApiError> {
+        self.inner.history(id).await
+    }
+
+    async fn replies(&self, feedback_id: uuid::Uuid) -> Result<Vec<FeedbackReplyModel>, ApiError> {
+        self.inner.replies(feedback_id).await
+    }
+
+    async fn create(
+        &self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        self.inner.create(body, default_rating, actor).await
+    }
+
+    async fn create_with_reply(
+        &self,
+        body: &CreateFeedbackSchema,
+        reply: &str,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<(FeedbackModel, FeedbackReplyModel), ApiError> {
+        self.inner
+            .create_with_reply(body, reply, default_rating, actor)
+            .await
+    }
+
+    async fn create_batch(
+        &self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.inner.create_batch(bodies, default_rating, actor).await
+    }
+
+    async fn begin_import(
+        &self,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Box<dyn FeedbackImport + '_>, ApiError> {
+        self.inner.begin_import(default_rating, actor).await
+    }
+
+    async fn replace(
+        &self,
+        id: uuid::Uuid,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Option<FeedbackModel>, ApiError> {
+        let feedback = self.inner.replace(id, body, default_rating, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn update(
+        &self,
+        id: uuid::Uuid,
+        body: &UpdateFeedbackSchema,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let feedback = self.inner.update(id, body, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn set_rating(
+        &self,
+        ids: &[uuid::Uuid],
+        rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let updated = self.inner.set_rating(ids, rating, actor).await?;
+        for feedback in &updated {
+            self.gets.forget(&feedback.id);
+        }
+
+        Ok(updated)
+    }
+
+    async fn vote(
+        &self,
+        id: uuid::Uuid,
+        upvotes: i32,
+        downvotes: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let feedback = self.inner.vote(id, upvotes, downvotes, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn touch(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let feedback = self.inner.touch(id, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn anonymize(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let feedback = self.inner.anonymize(id, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn restore(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let feedback = self.inner.restore(id, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(feedback)
+    }
+
+    async fn delete(&self, id: uuid::Uuid, actor: &str) -> Result<(), ApiError> {
+        self.inner.delete(id, actor).await?;
+        self.gets.forget(&id);
+
+        Ok(())
+    }
+
+    async fn delete_many(
+        &self,
+        ids: &[uuid::Uuid],
+        strict: bool,
+        actor: &str,
+    ) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let deleted = self.inner.delete_many(ids, strict, actor).await?;
+        for id in &deleted {
+            self.gets.forget(id);
+        }
+
+        Ok(deleted)
+    }
+
+    async fn delete_all(&self) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let deleted = self.inner.delete_all().await?;
+        for id in &deleted {
+            self.gets.forget(id);
+        }
+
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self, retention_days: i32) -> Result<u64, ApiError> {
+        // Which ids went is not reported, and purges are rare enough that
+        // retiring every read in flight costs nothing.
+        let purged = self.inner.purge_expired(retention_days).await?;
+        self.gets.forget_all();
+
+        Ok(purged)
+    }
+
+    async fn create_reply(
+        &self,
+        feedback_id: uuid::Uuid,
+        text: &str,
+        actor: &str,
+    ) -> Result<FeedbackReplyModel, ApiError> {
+        self.inner.create_reply(feedback_id, text, actor).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::future::join_all;
+
+    use super::*;
+
+    fn body(text: &str) -> CreateFeedbackSchema {
+        serde_json::from_value(serde_json::json!({ "text": text, "rating": 4 })).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn concurrent_gets_share_queries() {
+        let repo = SingleFlightRepository::new(InMemoryFeedbackRepository::with_get_latency(
+            Duration::from_millis(50),
+        ));
+        let feedback = repo.create(&body("Shared"), 3, "user-1").await.unwrap();
+
+        let results = join_all((0..100).map(|_| repo.get(feedback.id))).await;
+
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Ok(Some(found)) if found.id == feedback.id)));
+        assert!(repo.inner.get_count() < 10);
+    }
+
+    #[actix_web::test]
+    async fn writes_retire_gets_in_flight() {
+        let repo = SingleFlightRepository::new(InMemoryFeedbackRepository::with_get_latency(
+            Duration::from_millis(50),
+        ));
+        let feedback = repo.create(&body("Voted"), 3, "user-1").await.unwrap();
+
+        let (before, after) = tokio::join!(repo.get(feedback.id), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            repo.vote(feedback.id, 1, 0, "user-1").await.unwrap();
+            repo.get(feedback.id).await
+        });
+
+        assert_eq!(before.unwrap().unwrap().upvotes, 0);
+        assert_eq!(after.unwrap().unwrap().upvotes, 1);
+        assert_eq!(repo.inner.get_count(), 2);
+    }
+}
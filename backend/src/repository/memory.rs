@@ -0,0 +1,906 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+    time,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use common::{FeedbackCategoryCount, FeedbackTrendPoint};
+use futures_util::stream::{self, BoxStream, StreamExt};
+
+use super::{
+    missing_ids, not_found_error, trigram::similarity, FeedbackImport, FeedbackRepository,
+    PoolStatus, RatingOrder, WindowCounts, UNCATEGORIZED,
+};
+use crate::{
+    audit::{redacted_text, AuditAction},
+    error::ApiError,
+    filter::ValidatedFilter,
+    model::{AuditEntryModel, FeedbackModel, FeedbackReplyModel},
+    schema::{CreateFeedbackSchema, FilterOptions, TrendBucket, UpdateFeedbackSchema, MIN_RATING},
+};
+
+/// A `FeedbackRepository` kept in memory, for handler tests that should not
+/// need Postgres.
+///
+/// Behaves like `PgFeedbackRepository` where tests can tell: live text is
+/// unique ignoring case, soft-deleted feedback is hidden, and every write is
+/// audited. Full-text search is approximated by requiring every search word
+/// to appear in the text, with matches by id rather than relevance, and
+/// `similarity` by the same trigram measure `pg_trgm` uses.
+#[derive(Default)]
+pub struct InMemoryFeedbackRepository {
+    state: Mutex<State>,
+    /// How long `get` takes to answer after reading, so concurrent calls
+    /// overlap like real queries.
+    get_latency: time::Duration,
+    gets: AtomicUsize,
+}
+
+#[derive(Default)]
+struct State {
+    feedbacks: Vec<FeedbackModel>,
+    replies: Vec<FeedbackReplyModel>,
+    audit_log: Vec<AuditEntryModel>,
+}
+
+impl InMemoryFeedbackRepository {
+    pub fn with_get_latency(get_latency: time::Duration) -> Self {
+        Self {
+            get_latency,
+            ..Self::default()
+        }
+    }
+
+    /// How many times `get` reached this repository.
+    pub fn get_count(&self) -> usize {
+        self.gets.load(Ordering::SeqCst)
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl State {
+    fn live(&self) -> impl Iterator<Item = &FeedbackModel> {
+        self.feedbacks
+            .iter()
+            .filter(|feedback| feedback.deleted_at.is_none())
+    }
+
+    fn find(&self, id: uuid::Uuid) -> Option<&FeedbackModel> {
+        self.feedbacks.iter().find(|feedback| feedback.id == id)
+    }
+
+    fn find_live(&self, id: uuid::Uuid) -> Result<&FeedbackModel, ApiError> {
+        self.live()
+            .find(|feedback| feedback.id == id)
+            .ok_or(ApiError::FeedbackNotFound(id))
+    }
+
+    /// Fails like the unique index on `LOWER(text)` when another live
+    /// feedback already has `text`.
+    fn check_unique(&self, text: &str, except: Option<uuid::Uuid>) -> Result<(), ApiError> {
+        match self.live().find(|feedback| {
+            Some(feedback.id) != except && feedback.text.to_lowercase() == text.to_lowercase()
+        }) {
+            Some(_) => Err(ApiError::Duplicate(None)),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies `change` to the feedback `id` and audits it as `action`.
+    fn write(
+        &mut self,
+        id: uuid::Uuid,
+        action: AuditAction,
+        actor: &str,
+        change: impl FnOnce(&mut FeedbackModel),
+    ) -> Result<FeedbackModel, ApiError> {
+        let feedback = self
+            .feedbacks
+            .iter_mut()
+            .find(|feedback| feedback.id == id)
+            .ok_or(ApiError::FeedbackNotFound(id))?;
+
+        let before = feedback.clone();
+        change(feedback);
+        let after = feedback.clone();
+
+        self.record(action, actor, Some(&before), Some(&after));
+
+        Ok(after)
+    }
+
+    fn insert(
+        &mut self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        self.check_unique(&body.text, None)?;
+
+        let now = Utc::now();
+        let feedback = FeedbackModel {
+            id: uuid::Uuid::new_v4(),
+            text: body.text.to_string(),
+            rating: body.rating.unwrap_or(default_rating),
+            category: body.category.clone(),
+            author_email: body.author_email.clone(),
+            metadata: body.metadata.clone(),
+            upvotes: 0,
+            downvotes: 0,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        };
+
+        self.feedbacks.push(feedback.clone());
+        self.record(AuditAction::Create, actor, None, Some(&feedback));
+
+        Ok(feedback)
+    }
+
+    fn insert_all(
+        &mut self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let (feedbacks, replies, audit_log) = (
+            self.feedbacks.len(),
+            self.replies.len(),
+            self.audit_log.len(),
+        );
+
+        let inserted: Result<Vec<FeedbackModel>, ApiError> = bodies
+            .iter()
+            .map(|body| self.insert(body, default_rating, actor))
+            .collect();
+
+        if inserted.is_err() {
+            self.feedbacks.truncate(feedbacks);
+            self.replies.truncate(replies);
+            self.audit_log.truncate(audit_log);
+        }
+
+        inserted
+    }
+
+    fn record(
+        &mut self,
+        action: AuditAction,
+        actor: &str,
+        before: Option<&FeedbackModel>,
+        after: Option<&FeedbackModel>,
+    ) {
+        let Some(feedback_id) = after.or(before).map(|feedback| feedback.id) else {
+            return;
+        };
+
+        let to_json = |feedback: &FeedbackModel| serde_json::to_value(feedback).unwrap_or_default();
+
+        self.audit_log.push(AuditEntryModel {
+            id: uuid::Uuid::new_v4(),
+            feedback_id,
+            action: action.as_str().to_string(),
+            actor: actor.to_string(),
+            before: before.map(to_json),
+            after: after.map(to_json),
+            created_at: Utc::now(),
+        });
+    }
+
+    fn redact(&mut self, feedback_id: uuid::Uuid) {
+        let placeholder = serde_json::Value::from(redacted_text(feedback_id));
+
+        for entry in &mut self.audit_log {
+            if entry.feedback_id != feedback_id {
+                continue;
+            }
+
+            for snapshot in [&mut entry.before, &mut entry.after] {
+                if let Some(object) = snapshot.as_mut().and_then(|value| value.as_object_mut()) {
+                    object.remove("authorEmail");
+                    object.remove("metadata");
+                    object.insert("text".to_string(), placeholder.clone());
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, ids: &HashSet<uuid::Uuid>) {
+        self.feedbacks
+            .retain(|feedback| !ids.contains(&feedback.id));
+        self.replies
+            .retain(|reply| !ids.contains(&reply.feedback_id));
+    }
+}
+
+fn matches(feedback: &FeedbackModel, opts: &FilterOptions) -> bool {
+    let search_matches = opts.search_term().map_or(true, |search| {
+        let text = feedback.text.to_lowercase();
+        search
+            .split_whitespace()
+            .all(|word| text.contains(&word.to_lowercase()))
+    });
+    let metadata_matches = opts.metadata.as_ref().map_or(true, |wanted| {
+        let (Some(wanted), Some(metadata)) = (
+            wanted.as_object(),
+            feedback
+                .metadata
+                .as_ref()
+                .and_then(|value| value.as_object()),
+        ) else {
+            return false;
+        };
+
+        wanted
+            .iter()
+            .all(|(key, value)| metadata.get(key) == Some(value))
+    });
+    let sentiment_matches = opts.sentiment.map_or(true, |sentiment| {
+        let ratings = sentiment.ratings();
+        (*ratings.start() as i32..=*ratings.end() as i32).contains(&feedback.rating)
+    });
+
+    (opts.include_deleted.unwrap_or(false) || feedback.deleted_at.is_none())
+        && search_matches
+        && opts.rating.map_or(true, |rating| feedback.rating == rating)
+        && opts.min_rating.map_or(true, |min| feedback.rating >= min)
+        && opts.max_rating.map_or(true, |max| feedback.rating <= max)
+        && opts.category.as_ref().map_or(true, |category| {
+            feedback.category.as_ref() == Some(category)
+        })
+        && matches!(opts.created_after(), Ok(after) if after.map_or(true, |after| feedback.created_at >= after))
+        && matches!(opts.created_before(), Ok(before) if before.map_or(true, |before| feedback.created_at <= before))
+        && metadata_matches
+        && sentiment_matches
+}
+
+fn truncate(timestamp: DateTime<Utc>, bucket: TrendBucket) -> DateTime<Utc> {
+    let date = timestamp.date_naive();
+    let date = match bucket {
+        TrendBucket::Day => date,
+        TrendBucket::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        TrendBucket::Month => date.with_day(1).unwrap_or(date),
+    };
+
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn average(ratings: &[i32]) -> f64 {
+    round2(ratings.iter().map(|&rating| rating as f64).sum::<f64>() / ratings.len() as f64)
+}
+
+#[async_trait]
+impl FeedbackRepository for InMemoryFeedbackRepository {
+    async fn ping(&self) -> Result<(), ApiError> {
+        Ok(())
+    }
+
+    async fn check_ready(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    async fn list(&self, filter: &ValidatedFilter) -> Result<Vec<FeedbackModel>, ApiError> {
+        let state = self.state();
+        let mut feedbacks: Vec<FeedbackModel> = state
+            .feedbacks
+            .iter()
+            .filter(|feedback| matches(feedback, filter))
+            .filter(|feedback| filter.after.map_or(true, |after| feedback.id > after))
+            .cloned()
+            .collect();
+
+        feedbacks.sort_by(|a, b| {
+            let by_column = match filter.sort_column {
+                Some("rating") => a.rating.cmp(&b.rating),
+                Some("created_at") => a.created_at.cmp(&b.created_at),
+                Some("upvotes") => a.upvotes.cmp(&b.upvotes),
+                _ => a.id.cmp(&b.id),
+            };
+            let by_column = match filter.sort_order {
+                "DESC" => by_column.reverse(),
+                _ => by_column,
+            };
+
+            by_column.then(a.id.cmp(&b.id))
+        });
+
+        let offset = if filter.after.is_none() {
+            filter.offset
+        } else {
+            0
+        };
+
+        Ok(feedbacks
+            .into_iter()
+            .skip(offset)
+            .take(filter.limit)
+            .collect())
+    }
+
+    async fn count(&self, filter: &FilterOptions) -> Result<i64, ApiError> {
+        let state = self.state();
+
+        Ok(state
+            .feedbacks
+            .iter()
+            .filter(|feedback| matches(feedback, filter))
+            .count() as i64)
+    }
+
+    async fn get(&self, id: uuid::Uuid) -> Result<Option<FeedbackModel>, ApiError> {
+        self.gets.fetch_add(1, Ordering::SeqCst);
+        let feedback = self.state().find_live(id).ok().cloned();
+        tokio::time::sleep(self.get_latency).await;
+
+        Ok(feedback)
+    }
+
+    async fn get_many(&self, ids: &[uuid::Uuid]) -> Result<Vec<FeedbackModel>, ApiError> {
+        let state = self.state();
+
+        Ok(ids
+            .iter()
+            .filter_map(|&id| state.find_live(id).ok().cloned())
+            .collect())
+    }
+
+    async fn recent(
+        &self,
+        days: i32,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let since = Utc::now() - Duration::days(days as i64);
+        let mut feedbacks: Vec<FeedbackModel> = self
+            .state()
+            .live()
+            .filter(|feedback| feedback.updated_at > since)
+            .cloned()
+            .collect();
+
+        feedbacks.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then(a.id.cmp(&b.id)));
+
+        Ok(feedbacks.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn count_recent(&self, days: i32) -> Result<i64, ApiError> {
+        let since = Utc::now() - Duration::days(days as i64);
+
+        Ok(self
+            .state()
+            .live()
+            .filter(|feedback| feedback.updated_at > since)
+            .count() as i64)
+    }
+
+    async fn ranked(
+        &self,
+        order: RatingOrder,
+        limit: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut feedbacks: Vec<FeedbackModel> = self.state().live().cloned().collect();
+
+        feedbacks.sort_by(|a, b| {
+            let by_rating = match order {
+                RatingOrder::Lowest => a.rating.cmp(&b.rating),
+                RatingOrder::Highest => b.rating.cmp(&a.rating),
+            };
+
+            by_rating
+                .then(b.created_at.cmp(&a.created_at))
+                .then(a.id.cmp(&b.id))
+        });
+        feedbacks.truncate(limit);
+
+        Ok(feedbacks)
+    }
+
+    async fn rating_counts(&self) -> Result<Vec<(i32, i64)>, ApiError> {
+        let mut counts = BTreeMap::new();
+
+        for feedback in self.state().live() {
+            *counts.entry(feedback.rating).or_insert(0) += 1;
+        }
+
+        Ok(counts.into_iter().collect())
+    }
+
+    async fn trend(
+        &self,
+        bucket: TrendBucket,
+        days: i32,
+    ) -> Result<Vec<FeedbackTrendPoint>, ApiError> {
+        let since = Utc::now() - Duration::days(days as i64);
+        let mut buckets: BTreeMap<DateTime<Utc>, Vec<i32>> = BTreeMap::new();
+
+        for feedback in self.state().live() {
+            if feedback.created_at > since {
+                buckets
+                    .entry(truncate(feedback.created_at, bucket))
+                    .or_default()
+                    .push(feedback.rating);
+            }
+        }
+
+        Ok(buckets
+            .into_iter()
+            .map(|(bucket, ratings)| FeedbackTrendPoint {
+                bucket,
+                count: ratings.len() as i64,
+                average: average(&ratings),
+            })
+            .collect())
+    }
+
+    async fn category_counts(&self) -> Result<Vec<FeedbackCategoryCount>, ApiError> {
+        let mut categories: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+
+        for feedback in self.state().live() {
+            let category = feedback.category.as_deref().unwrap_or(UNCATEGORIZED);
+            categories
+                .entry(category.to_string())
+                .or_default()
+                .push(feedback.rating);
+        }
+
+        let mut counts: Vec<FeedbackCategoryCount> = categories
+            .into_iter()
+            .map(|(category, ratings)| FeedbackCategoryCount {
+                category,
+                count: ratings.len() as i64,
+                average_rating: average(&ratings),
+            })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.category.cmp(&b.category)));
+
+        Ok(counts)
+    }
+
+    async fn low_rating_counts(
+        &self,
+        window_mins: i32,
+        baseline_days: i32,
+    ) -> Result<WindowCounts, ApiError> {
+        let window_start = Utc::now() - Duration::minutes(window_mins as i64);
+        let baseline_start = window_start - Duration::days(baseline_days as i64);
+        let state = self.state();
+        let low = state
+            .feedbacks
+            .iter()
+            .filter(|feedback| feedback.rating == MIN_RATING);
+
+        let (current, baseline) = low.fold((0, 0), |(current, baseline), feedback| {
+            if feedback.created_at > window_start {
+                (current + 1, baseline)
+            } else if feedback.created_at > baseline_start {
+                (current, baseline + 1)
+            } else {
+                (current, baseline)
+            }
+        });
+
+        Ok(WindowCounts { current, baseline })
+    }
+
+    async fn find_by_text(&self, text: &str) -> Result<Option<uuid::Uuid>, ApiError> {
+        Ok(self
+            .state()
+            .live()
+            .find(|feedback| feedback.text.to_lowercase() == text.to_lowercase())
+            .map(|feedback| feedback.id))
+    }
+
+    async fn most_similar(
+        &self,
+        text: &str,
+        threshold: f32,
+    ) -> Result<Option<uuid::Uuid>, ApiError> {
+        Ok(self
+            .state()
+            .live()
+            .map(|feedback| (feedback.id, similarity(&feedback.text, text)))
+            .filter(|&(_, score)| score > threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id))
+    }
+
+    async fn similar_to(
+        &self,
+        feedback: &FeedbackModel,
+        limit: usize,
+    ) -> Result<Vec<(FeedbackModel, f32)>, ApiError> {
+        let mut similar: Vec<(FeedbackModel, f32)> = self
+            .state()
+            .live()
+            .filter(|other| other.id != feedback.id)
+            .map(|other| (other.clone(), similarity(&other.text, &feedback.text)))
+            .filter(|&(_, score)| score > 0.0)
+            .collect();
+
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.id.cmp(&b.0.id)));
+        similar.truncate(limit);
+
+        Ok(similar)
+    }
+
+    fn stream_live(&self) -> BoxStream<'_, Result<FeedbackModel, ApiError>> {
+        let mut feedbacks: Vec<FeedbackModel> = self.state().live().cloned().collect();
+        feedbacks.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        stream::iter(feedbacks.into_iter().map(Ok)).boxed()
+    }
+
+    async fn history(&self, id: uuid::Uuid) -> Result<Vec<AuditEntryModel>, ApiError> {
+        let state = self.state();
+        state.find(id).ok_or(ApiError::FeedbackNotFound(id))?;
+
+        Ok(state
+            .audit_log
+            .iter()
+            .filter(|entry| entry.feedback_id == id)
+            .cloned()
+            .collect())
+    }
+
+    async fn replies(&self, feedback_id: uuid::Uuid) -> Result<Vec<FeedbackReplyModel>, ApiError> {
+        let state = self.state();
+        state.find_live(feedback_id)?;
+
+        Ok(state
+            .replies
+            .iter()
+            .filter(|reply| reply.feedback_id == feedback_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn create(
+        &self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        self.state().insert(body, default_rating, actor)
+    }
+
+    async fn create_with_reply(
+        &self,
+        body: &CreateFeedbackSchema,
+        reply: &str,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<(FeedbackModel, FeedbackReplyModel), ApiError> {
+        let mut state = self.state();
+        let feedback = state.insert(body, default_rating, actor)?;
+        let reply = FeedbackReplyModel {
+            id: uuid::Uuid::new_v4(),
+            feedback_id: feedback.id,
+            text: reply.to_string(),
+            created_at: Utc::now(),
+        };
+        state.replies.push(reply.clone());
+
+        Ok((feedback, reply))
+    }
+
+    async fn create_batch(
+        &self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        self.state().insert_all(bodies, default_rating, actor)
+    }
+
+    async fn begin_import(
+        &self,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Box<dyn FeedbackImport + '_>, ApiError> {
+        Ok(Box::new(MemoryImport {
+            repo: self,
+            inserted: Vec::new(),
+            default_rating,
+            actor: actor.to_string(),
+        }))
+    }
+
+    async fn replace(
+        &self,
+        id: uuid::Uuid,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Option<FeedbackModel>, ApiError> {
+        let mut state = self.state();
+
+        if state.find_live(id).is_err() {
+            return Ok(None);
+        }
+        state.check_unique(&body.text, Some(id))?;
+
+        state
+            .write(id, AuditAction::Update, actor, |feedback| {
+                feedback.text = body.text.to_string();
+                feedback.rating = body.rating.unwrap_or(default_rating);
+                feedback.category.clone_from(&body.category);
+                feedback.author_email.clone_from(&body.author_email);
+                feedback.metadata.clone_from(&body.metadata);
+                feedback.updated_at = Utc::now();
+            })
+            .map(Some)
+    }
+
+    async fn update(
+        &self,
+        id: uuid::Uuid,
+        body: &UpdateFeedbackSchema,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut state = self.state();
+        let before = state.find_live(id)?;
+
+        if body.is_empty() {
+            return Ok(before.clone());
+        }
+
+        if body
+            .updated_at
+            .map_or(false, |updated_at| updated_at != before.updated_at)
+        {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} was modified by another request",
+                id
+            )));
+        }
+
+        if let Some(text) = &body.text {
+            state.check_unique(text, Some(id))?;
+        }
+
+        state.write(id, AuditAction::Update, actor, |feedback| {
+            if let Some(text) = &body.text {
+                feedback.text = text.to_string();
+            }
+            if let Some(rating) = body.rating {
+                feedback.rating = rating;
+            }
+            if let Some(category) = &body.category {
+                feedback.category = Some(category.clone());
+            }
+            feedback.updated_at = Utc::now();
+        })
+    }
+
+    async fn set_rating(
+        &self,
+        ids: &[uuid::Uuid],
+        rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut state = self.state();
+        let matched: Vec<uuid::Uuid> = state
+            .live()
+            .filter(|feedback| ids.contains(&feedback.id))
+            .map(|feedback| feedback.id)
+            .collect();
+
+        matched
+            .into_iter()
+            .map(|id| {
+                state.write(id, AuditAction::Update, actor, |feedback| {
+                    feedback.rating = rating;
+                    feedback.updated_at = Utc::now();
+                })
+            })
+            .collect()
+    }
+
+    async fn vote(
+        &self,
+        id: uuid::Uuid,
+        upvotes: i32,
+        downvotes: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut state = self.state();
+        state.find_live(id)?;
+
+        state.write(id, AuditAction::Vote, actor, |feedback| {
+            feedback.upvotes += upvotes;
+            feedback.downvotes += downvotes;
+        })
+    }
+
+    async fn touch(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut state = self.state();
+        state.find_live(id)?;
+
+        state.write(id, AuditAction::Touch, actor, |feedback| {
+            feedback.updated_at = Utc::now();
+        })
+    }
+
+    async fn anonymize(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut state = self.state();
+
+        let feedback = state.write(id, AuditAction::Anonymize, actor, |feedback| {
+            feedback.text = redacted_text(id);
+            feedback.author_email = None;
+            feedback.metadata = None;
+        })?;
+        state.redact(id);
+
+        Ok(feedback)
+    }
+
+    async fn restore(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut state = self.state();
+        let before = state.find(id).ok_or(ApiError::FeedbackNotFound(id))?;
+
+        if before.deleted_at.is_none() {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} is not deleted",
+                id
+            )));
+        }
+
+        state.write(id, AuditAction::Restore, actor, |feedback| {
+            feedback.deleted_at = None;
+        })
+    }
+
+    async fn delete(&self, id: uuid::Uuid, actor: &str) -> Result<(), ApiError> {
+        let mut state = self.state();
+        state.find_live(id)?;
+
+        state.write(id, AuditAction::Delete, actor, |feedback| {
+            feedback.deleted_at = Some(Utc::now());
+        })?;
+
+        Ok(())
+    }
+
+    async fn delete_many(
+        &self,
+        ids: &[uuid::Uuid],
+        strict: bool,
+        actor: &str,
+    ) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let mut state = self.state();
+        let matched: Vec<uuid::Uuid> = state
+            .live()
+            .filter(|feedback| ids.contains(&feedback.id))
+            .map(|feedback| feedback.id)
+            .collect();
+
+        if strict {
+            let not_found = missing_ids(ids, &matched);
+
+            if !not_found.is_empty() {
+                return Err(not_found_error(&not_found));
+            }
+        }
+
+        for &id in &matched {
+            state.write(id, AuditAction::Delete, actor, |feedback| {
+                feedback.deleted_at = Some(Utc::now());
+            })?;
+        }
+
+        Ok(matched)
+    }
+
+    async fn delete_all(&self) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let mut state = self.state();
+        let deleted = state.feedbacks.iter().map(|feedback| feedback.id).collect();
+        state.feedbacks.clear();
+        state.replies.clear();
+
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self, retention_days: i32) -> Result<u64, ApiError> {
+        let cutoff = Utc::now() - Duration::days(retention_days as i64);
+        let mut state = self.state();
+        let expired: HashSet<uuid::Uuid> = state
+            .feedbacks
+            .iter()
+            .filter(|feedback| feedback.created_at < cutoff)
+            .map(|feedback| feedback.id)
+            .collect();
+
+        state.remove(&expired);
+        for &id in &expired {
+            state.redact(id);
+        }
+
+        Ok(expired.len() as u64)
+    }
+
+    async fn create_reply(
+        &self,
+        feedback_id: uuid::Uuid,
+        text: &str,
+        actor: &str,
+    ) -> Result<FeedbackReplyModel, ApiError> {
+        let mut state = self.state();
+        let feedback = state.find_live(feedback_id)?.clone();
+        state.record(AuditAction::Reply, actor, Some(&feedback), Some(&feedback));
+
+        let reply = FeedbackReplyModel {
+            id: uuid::Uuid::new_v4(),
+            feedback_id,
+            text: text.to_string(),
+            created_at: Utc::now(),
+        };
+        state.replies.push(reply.clone());
+
+        Ok(reply)
+    }
+}
+
+/// Inserts as it goes and takes everything back out again unless committed,
+/// like a transaction that is rolled back on drop.
+struct MemoryImport<'a> {
+    repo: &'a InMemoryFeedbackRepository,
+    inserted: Vec<uuid::Uuid>,
+    default_rating: i32,
+    actor: String,
+}
+
+#[async_trait]
+impl FeedbackImport for MemoryImport<'_> {
+    async fn insert(&mut self, bodies: &[CreateFeedbackSchema]) -> Result<Vec<usize>, ApiError> {
+        let mut state = self.repo.state();
+        let mut duplicates = Vec::new();
+
+        for (index, body) in bodies.iter().enumerate() {
+            match state.insert(body, self.default_rating, &self.actor) {
+                Ok(feedback) => self.inserted.push(feedback.id),
+                Err(ApiError::Duplicate(_)) => duplicates.push(index),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(duplicates)
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), ApiError> {
+        self.inserted.clear();
+
+        Ok(())
+    }
+}
+
+impl Drop for MemoryImport<'_> {
+    fn drop(&mut self) {
+        if self.inserted.is_empty() {
+            return;
+        }
+
+        let inserted: HashSet<uuid::Uuid> = self.inserted.drain(..).collect();
+        let mut state = self.repo.state();
+        state.remove(&inserted);
+        state
+            .audit_log
+            .retain(|entry| !inserted.contains(&entry.feedback_id));
+    }
+}
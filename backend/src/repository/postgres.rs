@@ -0,0 +1,1116 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use common::{FeedbackCategoryCount, FeedbackTrendPoint};
+use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
+use sqlx::{Acquire, PgPool, Postgres, QueryBuilder, Transaction};
+
+use super::{
+    missing_ids, not_found_error, FeedbackImport, FeedbackRepository, PoolStatus, RatingOrder,
+    WindowCounts, UNCATEGORIZED,
+};
+use crate::{
+    audit::{self, AuditAction},
+    db::{self, RetryPolicy},
+    error::ApiError,
+    filter::ValidatedFilter,
+    model::{AuditEntryModel, FeedbackModel, FeedbackReplyModel},
+    schema::{CreateFeedbackSchema, FilterOptions, TrendBucket, UpdateFeedbackSchema, MIN_RATING},
+};
+
+pub struct PgFeedbackRepository {
+    pool: PgPool,
+    retry: RetryPolicy,
+}
+
+impl PgFeedbackRepository {
+    pub fn new(pool: PgPool, retry: RetryPolicy) -> Self {
+        Self { pool, retry }
+    }
+}
+
+fn push_filters(query: &mut QueryBuilder<Postgres>, opts: &FilterOptions) {
+    if !opts.include_deleted.unwrap_or(false) {
+        query.push(" AND deleted_at IS NULL");
+    }
+
+    if let Some(search) = opts.search_term() {
+        query
+            .push(" AND to_tsvector('english', text) @@ plainto_tsquery('english', ")
+            .push_bind(search.to_string())
+            .push(")");
+    }
+
+    if let Some(rating) = opts.rating {
+        query.push(" AND rating = ").push_bind(rating);
+    }
+
+    if let Some(min_rating) = opts.min_rating {
+        query.push(" AND rating >= ").push_bind(min_rating);
+    }
+
+    if let Some(max_rating) = opts.max_rating {
+        query.push(" AND rating <= ").push_bind(max_rating);
+    }
+
+    if let Some(category) = &opts.category {
+        query
+            .push(" AND category = ")
+            .push_bind(category.to_string());
+    }
+
+    // Both timestamps have already been checked by `FilterOptions::validate`.
+    if let Ok(Some(created_after)) = opts.created_after() {
+        query.push(" AND created_at >= ").push_bind(created_after);
+    }
+
+    if let Ok(Some(created_before)) = opts.created_before() {
+        query.push(" AND created_at <= ").push_bind(created_before);
+    }
+
+    if let Some(metadata) = &opts.metadata {
+        query.push(" AND metadata @> ").push_bind(metadata.clone());
+    }
+
+    if let Some(sentiment) = opts.sentiment {
+        let ratings = sentiment.ratings();
+        query
+            .push(" AND rating BETWEEN ")
+            .push_bind(*ratings.start() as i32)
+            .push(" AND ")
+            .push_bind(*ratings.end() as i32);
+    }
+}
+
+async fn insert_feedback(
+    tx: &mut Transaction<'_, Postgres>,
+    body: &CreateFeedbackSchema,
+    default_rating: i32,
+    actor: &str,
+) -> Result<FeedbackModel, ApiError> {
+    let feedback = sqlx::query_as!(
+        FeedbackModel,
+        "INSERT INTO feedbacks (text, rating, category, author_email, metadata)
+        VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        body.text,
+        body.rating.unwrap_or(default_rating),
+        body.category,
+        body.author_email,
+        body.metadata
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::record(tx, AuditAction::Create, actor, None, Some(&feedback)).await?;
+
+    Ok(feedback)
+}
+
+/// Inserts every feedback in `bodies` with one statement, auditing each.
+/// Returns them in the order of `bodies`.
+async fn insert_feedback_batch(
+    tx: &mut Transaction<'_, Postgres>,
+    bodies: &[CreateFeedbackSchema],
+    default_rating: i32,
+    actor: &str,
+) -> Result<Vec<FeedbackModel>, ApiError> {
+    // Ids are picked here rather than by the column default so the rows can
+    // be put back in input order: `RETURNING` does not promise one.
+    let ids: Vec<uuid::Uuid> = bodies.iter().map(|_| uuid::Uuid::new_v4()).collect();
+    let texts: Vec<String> = bodies.iter().map(|body| body.text.to_string()).collect();
+    let ratings: Vec<i32> = bodies
+        .iter()
+        .map(|body| body.rating.unwrap_or(default_rating))
+        .collect();
+    let categories: Vec<Option<String>> = bodies.iter().map(|body| body.category.clone()).collect();
+    let author_emails: Vec<Option<String>> = bodies
+        .iter()
+        .map(|body| body.author_email.clone())
+        .collect();
+    let metadata: Vec<Option<serde_json::Value>> =
+        bodies.iter().map(|body| body.metadata.clone()).collect();
+
+    let inserted = sqlx::query_as!(
+        FeedbackModel,
+        "INSERT INTO feedbacks (id, text, rating, category, author_email, metadata)
+        SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::int[], $4::varchar[], $5::varchar[], $6::jsonb[])
+        RETURNING *",
+        &ids,
+        &texts,
+        &ratings,
+        &categories as &[Option<String>],
+        &author_emails as &[Option<String>],
+        &metadata as &[Option<serde_json::Value>]
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut by_id: HashMap<uuid::Uuid, FeedbackModel> = inserted
+        .into_iter()
+        .map(|feedback| (feedback.id, feedback))
+        .collect();
+    let feedbacks: Vec<FeedbackModel> = ids.iter().filter_map(|id| by_id.remove(id)).collect();
+
+    for feedback in &feedbacks {
+        audit::record(tx, AuditAction::Create, actor, None, Some(feedback)).await?;
+    }
+
+    Ok(feedbacks)
+}
+
+/// The live feedback with `id`, locked until `tx` ends so the audit log sees
+/// the same `before` the write does.
+async fn lock_feedback(
+    tx: &mut Transaction<'_, Postgres>,
+    id: uuid::Uuid,
+) -> Result<Option<FeedbackModel>, ApiError> {
+    let feedback = sqlx::query_as!(
+        FeedbackModel,
+        "SELECT * FROM feedbacks WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(feedback)
+}
+
+/// Like `lock_feedback`, but finds soft-deleted feedback too.
+async fn lock_any_feedback(
+    tx: &mut Transaction<'_, Postgres>,
+    id: uuid::Uuid,
+) -> Result<Option<FeedbackModel>, ApiError> {
+    let feedback = sqlx::query_as!(
+        FeedbackModel,
+        "SELECT * FROM feedbacks WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(feedback)
+}
+
+async fn ensure_feedback_exists(pool: &PgPool, feedback_id: uuid::Uuid) -> Result<(), ApiError> {
+    sqlx::query!(
+        "SELECT id FROM feedbacks WHERE id = $1 AND deleted_at IS NULL",
+        feedback_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|_| ())
+    .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))
+}
+
+#[async_trait]
+impl FeedbackRepository for PgFeedbackRepository {
+    async fn ping(&self) -> Result<(), ApiError> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn check_ready(&self) -> Result<(), String> {
+        db::check_ready(&self.pool).await
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+        })
+    }
+
+    async fn list(&self, filter: &ValidatedFilter) -> Result<Vec<FeedbackModel>, ApiError> {
+        let search = filter.search_term();
+
+        let feedbacks = db::with_retry(self.retry, || async {
+            let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM feedbacks WHERE TRUE");
+
+            push_filters(&mut query, filter);
+
+            if let Some(after) = filter.after {
+                query.push(" AND id > ").push_bind(after);
+            }
+
+            // Ordering precedence:
+            // 1. An explicit `sort_by` always wins, even when searching.
+            // 2. Otherwise a search orders by relevance (`ts_rank`, best first), and
+            //    `order` is ignored.
+            // 3. Otherwise rows come back by id.
+            // `id` breaks ties in every case so pages are deterministic.
+            match (filter.sort_column, search) {
+                (Some("id") | None, None) => {
+                    query.push(format!(" ORDER BY id {}", filter.sort_order))
+                }
+                (Some(column), _) => query.push(format!(
+                    " ORDER BY {} {}, id",
+                    column, filter.sort_order
+                )),
+                (None, Some(search)) => query
+                    .push(
+                        " ORDER BY ts_rank(to_tsvector('english', text), plainto_tsquery('english', ",
+                    )
+                    .push_bind(search.to_string())
+                    .push(")) DESC, id"),
+            };
+
+            query.push(" LIMIT ").push_bind(filter.limit as i64);
+
+            if filter.after.is_none() {
+                query.push(" OFFSET ").push_bind(filter.offset as i64);
+            }
+
+            query
+                .build_query_as::<FeedbackModel>()
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn count(&self, filter: &FilterOptions) -> Result<i64, ApiError> {
+        let (total,): (i64,) = db::with_retry(self.retry, || async {
+            let mut query =
+                QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM feedbacks WHERE TRUE");
+            push_filters(&mut query, filter);
+
+            query.build_query_as().fetch_one(&self.pool).await
+        })
+        .await?;
+
+        Ok(total)
+    }
+
+    async fn get(&self, id: uuid::Uuid) -> Result<Option<FeedbackModel>, ApiError> {
+        let feedback = db::with_retry(self.retry, || {
+            sqlx::query_as!(
+                FeedbackModel,
+                "SELECT * FROM feedbacks WHERE id = $1 AND deleted_at IS NULL",
+                id,
+            )
+            .fetch_optional(&self.pool)
+        })
+        .await?;
+
+        Ok(feedback)
+    }
+
+    async fn get_many(&self, ids: &[uuid::Uuid]) -> Result<Vec<FeedbackModel>, ApiError> {
+        let feedbacks = db::with_retry(self.retry, || {
+            sqlx::query_as!(
+                FeedbackModel,
+                "SELECT * FROM feedbacks
+                WHERE id = ANY($1) AND deleted_at IS NULL
+                ORDER BY array_position($1, id)",
+                ids
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn recent(
+        &self,
+        days: i32,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let feedbacks = db::with_retry(self.retry, || {
+            sqlx::query_as!(
+                FeedbackModel,
+                "SELECT * FROM feedbacks
+                WHERE deleted_at IS NULL AND updated_at > NOW() - make_interval(days => $1)
+                ORDER BY updated_at DESC, id
+                LIMIT $2 OFFSET $3",
+                days,
+                limit as i64,
+                offset as i64
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn count_recent(&self, days: i32) -> Result<i64, ApiError> {
+        let total = db::with_retry(self.retry, || {
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM feedbacks
+                WHERE deleted_at IS NULL AND updated_at > NOW() - make_interval(days => $1)"#,
+                days
+            )
+            .fetch_one(&self.pool)
+        })
+        .await?;
+
+        Ok(total)
+    }
+
+    async fn ranked(
+        &self,
+        order: RatingOrder,
+        limit: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let feedbacks = match order {
+            RatingOrder::Lowest => {
+                db::with_retry(self.retry, || {
+                    sqlx::query_as!(
+                        FeedbackModel,
+                        "SELECT * FROM feedbacks WHERE deleted_at IS NULL
+                        ORDER BY rating ASC, created_at DESC, id
+                        LIMIT $1",
+                        limit as i64
+                    )
+                    .fetch_all(&self.pool)
+                })
+                .await?
+            }
+            RatingOrder::Highest => {
+                db::with_retry(self.retry, || {
+                    sqlx::query_as!(
+                        FeedbackModel,
+                        "SELECT * FROM feedbacks WHERE deleted_at IS NULL
+                        ORDER BY rating DESC, created_at DESC, id
+                        LIMIT $1",
+                        limit as i64
+                    )
+                    .fetch_all(&self.pool)
+                })
+                .await?
+            }
+        };
+
+        Ok(feedbacks)
+    }
+
+    async fn rating_counts(&self) -> Result<Vec<(i32, i64)>, ApiError> {
+        let rows = db::with_retry(self.retry, || {
+            sqlx::query!(
+                r#"SELECT rating, COUNT(*) AS "count!" FROM feedbacks
+                WHERE deleted_at IS NULL
+                GROUP BY rating"#
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.rating, row.count))
+            .collect())
+    }
+
+    async fn trend(
+        &self,
+        bucket: TrendBucket,
+        days: i32,
+    ) -> Result<Vec<FeedbackTrendPoint>, ApiError> {
+        let rows = db::with_retry(self.retry, || {
+            sqlx::query!(
+                r#"SELECT date_trunc($1, created_at) AS "bucket!",
+                    COUNT(*) AS "count!",
+                    ROUND(AVG(rating)::numeric, 2)::float8 AS "average!"
+                FROM feedbacks
+                WHERE deleted_at IS NULL AND created_at > NOW() - make_interval(days => $2)
+                GROUP BY 1
+                ORDER BY 1"#,
+                bucket.as_str(),
+                days
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FeedbackTrendPoint {
+                bucket: row.bucket,
+                count: row.count,
+                average: row.average,
+            })
+            .collect())
+    }
+
+    async fn category_counts(&self) -> Result<Vec<FeedbackCategoryCount>, ApiError> {
+        let rows = db::with_retry(self.retry, || {
+            sqlx::query!(
+                r#"SELECT COALESCE(category, $1) AS "category!",
+                    COUNT(*) AS "count!",
+                    ROUND(AVG(rating)::numeric, 2)::float8 AS "average_rating!"
+                FROM feedbacks
+                WHERE deleted_at IS NULL
+                GROUP BY 1
+                ORDER BY 2 DESC, 1"#,
+                UNCATEGORIZED
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FeedbackCategoryCount {
+                category: row.category,
+                count: row.count,
+                average_rating: row.average_rating,
+            })
+            .collect())
+    }
+
+    async fn low_rating_counts(
+        &self,
+        window_mins: i32,
+        baseline_days: i32,
+    ) -> Result<WindowCounts, ApiError> {
+        let row = db::with_retry(self.retry, || {
+            sqlx::query!(
+                r#"SELECT
+                    COUNT(*) FILTER (WHERE created_at > NOW() - make_interval(mins => $2)) AS "current!",
+                    COUNT(*) FILTER (WHERE created_at <= NOW() - make_interval(mins => $2)) AS "baseline!"
+                FROM feedbacks
+                WHERE rating = $1
+                    AND created_at > NOW() - make_interval(days => $3, mins => $2)"#,
+                MIN_RATING,
+                window_mins,
+                baseline_days
+            )
+            .fetch_one(&self.pool)
+        })
+        .await?;
+
+        Ok(WindowCounts {
+            current: row.current,
+            baseline: row.baseline,
+        })
+    }
+
+    async fn find_by_text(&self, text: &str) -> Result<Option<uuid::Uuid>, ApiError> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM feedbacks WHERE LOWER(text) = LOWER($1) AND deleted_at IS NULL",
+            text
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn most_similar(
+        &self,
+        text: &str,
+        threshold: f32,
+    ) -> Result<Option<uuid::Uuid>, ApiError> {
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM feedbacks
+            WHERE deleted_at IS NULL AND similarity(text, $1) > $2
+            ORDER BY similarity(text, $1) DESC
+            LIMIT 1",
+            text,
+            threshold
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn similar_to(
+        &self,
+        feedback: &FeedbackModel,
+        limit: usize,
+    ) -> Result<Vec<(FeedbackModel, f32)>, ApiError> {
+        let scores = db::with_retry(self.retry, || {
+            sqlx::query!(
+                r#"SELECT id, similarity(text, $1) AS "similarity!" FROM feedbacks
+                WHERE deleted_at IS NULL AND id <> $2 AND similarity(text, $1) > 0
+                ORDER BY 2 DESC, id
+                LIMIT $3"#,
+                feedback.text,
+                feedback.id,
+                limit as i64
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        let ids: Vec<uuid::Uuid> = scores.iter().map(|score| score.id).collect();
+
+        Ok(self
+            .get_many(&ids)
+            .await?
+            .into_iter()
+            .filter_map(|feedback| {
+                // Matched by id rather than position in case one was deleted in
+                // between the two queries.
+                let score = scores.iter().find(|score| score.id == feedback.id)?;
+
+                Some((feedback, score.similarity))
+            })
+            .collect())
+    }
+
+    fn stream_live(&self) -> BoxStream<'_, Result<FeedbackModel, ApiError>> {
+        sqlx::query_as!(
+            FeedbackModel,
+            "SELECT * FROM feedbacks WHERE deleted_at IS NULL ORDER BY created_at, id"
+        )
+        .fetch(&self.pool)
+        .map_err(ApiError::from)
+        .boxed()
+    }
+
+    async fn history(&self, id: uuid::Uuid) -> Result<Vec<AuditEntryModel>, ApiError> {
+        let entries = db::with_retry(self.retry, || audit::history(&self.pool, id)).await?;
+
+        // Feedback written before the audit log existed has no history.
+        if entries.is_empty() {
+            sqlx::query!("SELECT id FROM feedbacks WHERE id = $1", id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+        }
+
+        Ok(entries)
+    }
+
+    async fn replies(&self, feedback_id: uuid::Uuid) -> Result<Vec<FeedbackReplyModel>, ApiError> {
+        ensure_feedback_exists(&self.pool, feedback_id).await?;
+
+        let replies = db::with_retry(self.retry, || {
+            sqlx::query_as!(
+                FeedbackReplyModel,
+                "SELECT * FROM feedback_replies WHERE feedback_id = $1 ORDER BY created_at, id",
+                feedback_id
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(replies)
+    }
+
+    async fn create(
+        &self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let feedback = insert_feedback(&mut tx, body, default_rating, actor).await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn create_with_reply(
+        &self,
+        body: &CreateFeedbackSchema,
+        reply: &str,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<(FeedbackModel, FeedbackReplyModel), ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let feedback = insert_feedback(&mut tx, body, default_rating, actor).await?;
+
+        let reply = sqlx::query_as!(
+            FeedbackReplyModel,
+            "INSERT INTO feedback_replies (feedback_id, text) VALUES ($1, $2) RETURNING *",
+            feedback.id,
+            reply
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((feedback, reply))
+    }
+
+    async fn create_batch(
+        &self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let feedbacks = insert_feedback_batch(&mut tx, bodies, default_rating, actor).await?;
+        tx.commit().await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn begin_import(
+        &self,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Box<dyn FeedbackImport + '_>, ApiError> {
+        Ok(Box::new(PgImport {
+            tx: self.pool.begin().await?,
+            default_rating,
+            actor: actor.to_string(),
+        }))
+    }
+
+    async fn replace(
+        &self,
+        id: uuid::Uuid,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Option<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = lock_feedback(&mut tx, id).await? else {
+            return Ok(None);
+        };
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks
+            SET text = $1, rating = $2, category = $3, author_email = $4, metadata = $5,
+                updated_at = NOW()
+            WHERE id = $6 RETURNING *",
+            body.text,
+            body.rating.unwrap_or(default_rating),
+            body.category,
+            body.author_email,
+            body.metadata,
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Update,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(feedback))
+    }
+
+    async fn update(
+        &self,
+        id: uuid::Uuid,
+        body: &UpdateFeedbackSchema,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        if body.is_empty() {
+            return self
+                .get(id)
+                .await?
+                .ok_or_else(|| ApiError::FeedbackNotFound(id));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        if body
+            .updated_at
+            .map_or(false, |updated_at| updated_at != before.updated_at)
+        {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} was modified by another request",
+                id
+            )));
+        }
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks
+            SET text = COALESCE($1, text),
+                rating = COALESCE($2, rating),
+                category = COALESCE($3, category),
+                updated_at = NOW()
+            WHERE id = $4
+            RETURNING *",
+            body.text,
+            body.rating,
+            body.category,
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Update,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn set_rating(
+        &self,
+        ids: &[uuid::Uuid],
+        rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = sqlx::query_as!(
+            FeedbackModel,
+            "SELECT * FROM feedbacks WHERE id = ANY($1) AND deleted_at IS NULL FOR UPDATE",
+            ids
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        let updated = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET rating = $1, updated_at = NOW()
+            WHERE id = ANY($2) AND deleted_at IS NULL RETURNING *",
+            rating,
+            ids
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        for feedback in &updated {
+            let before = before.iter().find(|before| before.id == feedback.id);
+            audit::record(&mut tx, AuditAction::Update, actor, before, Some(feedback)).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    async fn vote(
+        &self,
+        id: uuid::Uuid,
+        upvotes: i32,
+        downvotes: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or(ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET upvotes = upvotes + $1, downvotes = downvotes + $2
+            WHERE id = $3 RETURNING *",
+            upvotes,
+            downvotes,
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Vote,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn touch(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or(ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET updated_at = NOW() WHERE id = $1 RETURNING *",
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Touch,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn anonymize(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let placeholder = audit::redacted_text(id);
+
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_any_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET text = $1, author_email = NULL, metadata = NULL
+            WHERE id = $2 RETURNING *",
+            placeholder,
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Anonymize,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        // Also scrubs the entry just recorded.
+        audit::redact(&mut tx, id, &placeholder).await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn restore(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_any_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        if before.deleted_at.is_none() {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} is not deleted",
+                id
+            )));
+        }
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET deleted_at = NULL WHERE id = $1 RETURNING *",
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Restore,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn delete(&self, id: uuid::Uuid, actor: &str) -> Result<(), ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET deleted_at = NOW() WHERE id = $1 RETURNING *",
+            id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Delete,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_many(
+        &self,
+        ids: &[uuid::Uuid],
+        strict: bool,
+        actor: &str,
+    ) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query_as!(
+            FeedbackModel,
+            "UPDATE feedbacks SET deleted_at = NOW()
+            WHERE id = ANY($1) AND deleted_at IS NULL RETURNING *",
+            ids
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        for feedback in &deleted {
+            let before = FeedbackModel {
+                deleted_at: None,
+                ..feedback.clone()
+            };
+            audit::record(
+                &mut tx,
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                Some(feedback),
+            )
+            .await?;
+        }
+
+        let deleted: Vec<uuid::Uuid> = deleted.iter().map(|feedback| feedback.id).collect();
+
+        if strict {
+            let not_found = missing_ids(ids, &deleted);
+
+            if !not_found.is_empty() {
+                tx.rollback().await?;
+
+                return Err(not_found_error(&not_found));
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn delete_all(&self) -> Result<Vec<uuid::Uuid>, ApiError> {
+        // One statement, so the ids are exactly what was removed even while
+        // other requests write.
+        let deleted = sqlx::query_scalar!("DELETE FROM feedbacks RETURNING id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self, retention_days: i32) -> Result<u64, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let purged = sqlx::query_scalar!(
+            "DELETE FROM feedbacks WHERE created_at < NOW() - make_interval(days => $1) RETURNING id",
+            retention_days
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        for &feedback_id in &purged {
+            audit::redact(&mut tx, feedback_id, &audit::redacted_text(feedback_id)).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(purged.len() as u64)
+    }
+
+    async fn create_reply(
+        &self,
+        feedback_id: uuid::Uuid,
+        text: &str,
+        actor: &str,
+    ) -> Result<FeedbackReplyModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let feedback = lock_feedback(&mut tx, feedback_id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))?;
+
+        let reply = sqlx::query_as!(
+            FeedbackReplyModel,
+            "INSERT INTO feedback_replies (feedback_id, text) VALUES ($1, $2) RETURNING *",
+            feedback_id,
+            text
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        audit::record(
+            &mut tx,
+            AuditAction::Reply,
+            actor,
+            Some(&feedback),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(reply)
+    }
+}
+
+struct PgImport {
+    tx: Transaction<'static, Postgres>,
+    default_rating: i32,
+    actor: String,
+}
+
+#[async_trait]
+impl FeedbackImport for PgImport {
+    async fn insert(&mut self, bodies: &[CreateFeedbackSchema]) -> Result<Vec<usize>, ApiError> {
+        // Most batches have no duplicates, so try the whole batch in one
+        // statement first, under a savepoint so a collision only undoes it.
+        let mut batch = self.tx.begin().await?;
+        match insert_feedback_batch(&mut batch, bodies, self.default_rating, &self.actor).await {
+            Ok(_) => {
+                batch.commit().await?;
+                return Ok(Vec::new());
+            }
+            Err(ApiError::Duplicate(_)) => batch.rollback().await?,
+            Err(e) => return Err(e),
+        }
+
+        // Then row by row to find out which ones collided.
+        let mut duplicates = Vec::new();
+        for (index, body) in bodies.iter().enumerate() {
+            let mut row = self.tx.begin().await?;
+            match insert_feedback(&mut row, body, self.default_rating, &self.actor).await {
+                Ok(_) => row.commit().await?,
+                Err(ApiError::Duplicate(_)) => {
+                    row.rollback().await?;
+                    duplicates.push(index);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(duplicates)
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), ApiError> {
+        self.tx.commit().await?;
+
+        Ok(())
+    }
+}
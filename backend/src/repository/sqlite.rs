@@ -0,0 +1,1302 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use common::{FeedbackCategoryCount, FeedbackTrendPoint};
+use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
+use sqlx::{Acquire, QueryBuilder, Sqlite, SqlitePool, Transaction};
+
+use super::{
+    missing_ids, not_found_error, trigram::similarity, FeedbackImport, FeedbackRepository,
+    PoolStatus, RatingOrder, WindowCounts, UNCATEGORIZED,
+};
+use crate::{
+    audit::{self, AuditAction},
+    db::{self, sqlite_timestamp, RetryPolicy},
+    error::ApiError,
+    filter::ValidatedFilter,
+    model::{AuditEntryModel, FeedbackModel, FeedbackReplyModel},
+    schema::{CreateFeedbackSchema, FilterOptions, TrendBucket, UpdateFeedbackSchema, MIN_RATING},
+};
+
+/// A `FeedbackRepository` on SQLite, for running the backend without
+/// Postgres.
+///
+/// Postgres features SQLite lacks are approximated: full-text search requires
+/// every search word to appear in the text, with matches by id rather than
+/// relevance, and `similarity` uses the same trigram measure as `pg_trgm`,
+/// computed here over the live feedback. `LOWER`, and so the unique text
+/// index, only folds ASCII letters.
+pub struct SqliteFeedbackRepository {
+    pool: SqlitePool,
+    retry: RetryPolicy,
+}
+
+impl SqliteFeedbackRepository {
+    pub fn new(pool: SqlitePool, retry: RetryPolicy) -> Self {
+        Self { pool, retry }
+    }
+}
+
+fn now() -> String {
+    sqlite_timestamp(Utc::now())
+}
+
+fn since(duration: Duration) -> String {
+    sqlite_timestamp(Utc::now() - duration)
+}
+
+fn push_filters(query: &mut QueryBuilder<Sqlite>, opts: &FilterOptions) {
+    if !opts.include_deleted.unwrap_or(false) {
+        query.push(" AND deleted_at IS NULL");
+    }
+
+    if let Some(search) = opts.search_term() {
+        for word in search.split_whitespace() {
+            query
+                .push(" AND instr(LOWER(text), LOWER(")
+                .push_bind(word.to_string())
+                .push(")) > 0");
+        }
+    }
+
+    if let Some(rating) = opts.rating {
+        query.push(" AND rating = ").push_bind(rating);
+    }
+
+    if let Some(min_rating) = opts.min_rating {
+        query.push(" AND rating >= ").push_bind(min_rating);
+    }
+
+    if let Some(max_rating) = opts.max_rating {
+        query.push(" AND rating <= ").push_bind(max_rating);
+    }
+
+    if let Some(category) = &opts.category {
+        query
+            .push(" AND category = ")
+            .push_bind(category.to_string());
+    }
+
+    // Both timestamps have already been checked by `FilterOptions::validate`.
+    if let Ok(Some(created_after)) = opts.created_after() {
+        query
+            .push(" AND created_at >= ")
+            .push_bind(sqlite_timestamp(created_after));
+    }
+
+    if let Ok(Some(created_before)) = opts.created_before() {
+        query
+            .push(" AND created_at <= ")
+            .push_bind(sqlite_timestamp(created_before));
+    }
+
+    if let Some(metadata) = &opts.metadata {
+        match metadata.as_object() {
+            Some(pairs) => {
+                for (key, value) in pairs {
+                    query
+                        .push(" AND json_extract(metadata, ")
+                        .push_bind(format!("$.\"{}\"", key))
+                        .push(") = json_extract(")
+                        .push_bind(value.to_string())
+                        .push(", '$')");
+                }
+            }
+            None => {
+                query.push(" AND FALSE");
+            }
+        }
+    }
+
+    if let Some(sentiment) = opts.sentiment {
+        let ratings = sentiment.ratings();
+        query
+            .push(" AND rating BETWEEN ")
+            .push_bind(*ratings.start() as i32)
+            .push(" AND ")
+            .push_bind(*ratings.end() as i32);
+    }
+}
+
+/// Appends `ids` as the list of an `IN`.
+fn push_ids(query: &mut QueryBuilder<Sqlite>, ids: &[uuid::Uuid]) {
+    query.push(" (");
+    let mut list = query.separated(", ");
+    for id in ids {
+        list.push_bind(*id);
+    }
+    query.push(")");
+}
+
+async fn insert_feedback(
+    tx: &mut Transaction<'_, Sqlite>,
+    body: &CreateFeedbackSchema,
+    default_rating: i32,
+    actor: &str,
+) -> Result<FeedbackModel, ApiError> {
+    let feedback = sqlx::query_as::<_, FeedbackModel>(
+        "INSERT INTO feedbacks (id, text, rating, category, author_email, metadata, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $7) RETURNING *",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(&body.text)
+    .bind(body.rating.unwrap_or(default_rating))
+    .bind(&body.category)
+    .bind(&body.author_email)
+    .bind(&body.metadata)
+    .bind(now())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record(tx, AuditAction::Create, actor, None, Some(&feedback)).await?;
+
+    Ok(feedback)
+}
+
+async fn insert_reply(
+    tx: &mut Transaction<'_, Sqlite>,
+    feedback_id: uuid::Uuid,
+    text: &str,
+) -> Result<FeedbackReplyModel, ApiError> {
+    let reply = sqlx::query_as::<_, FeedbackReplyModel>(
+        "INSERT INTO feedback_replies (id, feedback_id, text, created_at)
+        VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(feedback_id)
+    .bind(text)
+    .bind(now())
+    .fetch_one(tx)
+    .await?;
+
+    Ok(reply)
+}
+
+/// The live feedback with `id`. SQLite has no `FOR UPDATE`, so this writes
+/// the row back unchanged: that takes the database's write lock up front,
+/// which is what the rest of the transaction needs anyway, instead of a read
+/// lock that another writer could keep it from upgrading.
+async fn lock_feedback(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: uuid::Uuid,
+) -> Result<Option<FeedbackModel>, ApiError> {
+    let feedback = sqlx::query_as::<_, FeedbackModel>(
+        "UPDATE feedbacks SET rating = rating WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(feedback)
+}
+
+/// Like `lock_feedback`, but finds soft-deleted feedback too.
+async fn lock_any_feedback(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: uuid::Uuid,
+) -> Result<Option<FeedbackModel>, ApiError> {
+    let feedback = sqlx::query_as::<_, FeedbackModel>(
+        "UPDATE feedbacks SET rating = rating WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(feedback)
+}
+
+async fn ensure_feedback_exists(
+    pool: &SqlitePool,
+    feedback_id: uuid::Uuid,
+) -> Result<(), ApiError> {
+    sqlx::query("SELECT id FROM feedbacks WHERE id = $1 AND deleted_at IS NULL")
+        .bind(feedback_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))
+}
+
+/// `audit::record` for SQLite.
+async fn record(
+    tx: &mut Transaction<'_, Sqlite>,
+    action: AuditAction,
+    actor: &str,
+    before: Option<&FeedbackModel>,
+    after: Option<&FeedbackModel>,
+) -> Result<(), ApiError> {
+    let Some(feedback_id) = after.or(before).map(|feedback| feedback.id) else {
+        return Ok(());
+    };
+
+    let to_json = |feedback: &FeedbackModel| serde_json::to_value(feedback).unwrap_or_default();
+
+    sqlx::query(
+        "INSERT INTO audit_log (id, feedback_id, action, actor, before, after, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(feedback_id)
+    .bind(action.as_str())
+    .bind(actor)
+    .bind(before.map(to_json))
+    .bind(after.map(to_json))
+    .bind(now())
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+/// `audit::redact` for SQLite.
+async fn redact(
+    tx: &mut Transaction<'_, Sqlite>,
+    feedback_id: uuid::Uuid,
+    placeholder: &str,
+) -> Result<(), ApiError> {
+    sqlx::query(
+        "UPDATE audit_log
+        SET before = json_set(json_remove(before, '$.authorEmail', '$.metadata'), '$.text', $2),
+            after = json_set(json_remove(after, '$.authorEmail', '$.metadata'), '$.text', $2)
+        WHERE feedback_id = $1",
+    )
+    .bind(feedback_id)
+    .bind(placeholder)
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl FeedbackRepository for SqliteFeedbackRepository {
+    async fn ping(&self) -> Result<(), ApiError> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn check_ready(&self) -> Result<(), String> {
+        db::check_sqlite_ready(&self.pool).await
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+        })
+    }
+
+    async fn list(&self, filter: &ValidatedFilter) -> Result<Vec<FeedbackModel>, ApiError> {
+        let feedbacks = db::with_retry(self.retry, || async {
+            let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM feedbacks WHERE TRUE");
+
+            push_filters(&mut query, filter);
+
+            if let Some(after) = filter.after {
+                query.push(" AND id > ").push_bind(after);
+            }
+
+            // As on Postgres, except that a search without `sort_by` has no
+            // relevance to order by and comes back by id.
+            match filter.sort_column {
+                Some("id") | None => query.push(format!(" ORDER BY id {}", filter.sort_order)),
+                Some(column) => {
+                    query.push(format!(" ORDER BY {} {}, id", column, filter.sort_order))
+                }
+            };
+
+            query.push(" LIMIT ").push_bind(filter.limit as i64);
+
+            if filter.after.is_none() {
+                query.push(" OFFSET ").push_bind(filter.offset as i64);
+            }
+
+            query
+                .build_query_as::<FeedbackModel>()
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn count(&self, filter: &FilterOptions) -> Result<i64, ApiError> {
+        let (total,): (i64,) = db::with_retry(self.retry, || async {
+            let mut query =
+                QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM feedbacks WHERE TRUE");
+            push_filters(&mut query, filter);
+
+            query.build_query_as().fetch_one(&self.pool).await
+        })
+        .await?;
+
+        Ok(total)
+    }
+
+    async fn get(&self, id: uuid::Uuid) -> Result<Option<FeedbackModel>, ApiError> {
+        let feedback = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, FeedbackModel>(
+                "SELECT * FROM feedbacks WHERE id = $1 AND deleted_at IS NULL",
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+        })
+        .await?;
+
+        Ok(feedback)
+    }
+
+    async fn get_many(&self, ids: &[uuid::Uuid]) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut feedbacks = db::with_retry(self.retry, || async {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "SELECT * FROM feedbacks WHERE deleted_at IS NULL AND id IN",
+            );
+            push_ids(&mut query, ids);
+
+            query
+                .build_query_as::<FeedbackModel>()
+                .fetch_all(&self.pool)
+                .await
+        })
+        .await?;
+
+        feedbacks.sort_by_key(|feedback| ids.iter().position(|&id| id == feedback.id));
+
+        Ok(feedbacks)
+    }
+
+    async fn recent(
+        &self,
+        days: i32,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let since = since(Duration::days(days as i64));
+
+        let feedbacks = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, FeedbackModel>(
+                "SELECT * FROM feedbacks
+                WHERE deleted_at IS NULL AND updated_at > $1
+                ORDER BY updated_at DESC, id
+                LIMIT $2 OFFSET $3",
+            )
+            .bind(&since)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn count_recent(&self, days: i32) -> Result<i64, ApiError> {
+        let since = since(Duration::days(days as i64));
+
+        let total = db::with_retry(self.retry, || {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM feedbacks WHERE deleted_at IS NULL AND updated_at > $1",
+            )
+            .bind(&since)
+            .fetch_one(&self.pool)
+        })
+        .await?;
+
+        Ok(total)
+    }
+
+    async fn ranked(
+        &self,
+        order: RatingOrder,
+        limit: usize,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let sql = match order {
+            RatingOrder::Lowest => {
+                "SELECT * FROM feedbacks WHERE deleted_at IS NULL
+                ORDER BY rating ASC, created_at DESC, id
+                LIMIT $1"
+            }
+            RatingOrder::Highest => {
+                "SELECT * FROM feedbacks WHERE deleted_at IS NULL
+                ORDER BY rating DESC, created_at DESC, id
+                LIMIT $1"
+            }
+        };
+
+        let feedbacks = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, FeedbackModel>(sql)
+                .bind(limit as i64)
+                .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn rating_counts(&self) -> Result<Vec<(i32, i64)>, ApiError> {
+        let counts = db::with_retry(self.retry, || {
+            sqlx::query_as(
+                "SELECT rating, COUNT(*) FROM feedbacks
+                WHERE deleted_at IS NULL
+                GROUP BY rating",
+            )
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(counts)
+    }
+
+    async fn trend(
+        &self,
+        bucket: TrendBucket,
+        days: i32,
+    ) -> Result<Vec<FeedbackTrendPoint>, ApiError> {
+        // What `date_trunc` would give, weeks starting on Monday.
+        let start = match bucket {
+            TrendBucket::Day => "strftime('%Y-%m-%dT00:00:00Z', created_at)",
+            TrendBucket::Week => {
+                "strftime('%Y-%m-%dT00:00:00Z', created_at, 'weekday 0', '-6 days')"
+            }
+            TrendBucket::Month => "strftime('%Y-%m-01T00:00:00Z', created_at)",
+        };
+        let sql = format!(
+            "SELECT {}, COUNT(*), ROUND(AVG(rating), 2)
+            FROM feedbacks
+            WHERE deleted_at IS NULL AND created_at > $1
+            GROUP BY 1
+            ORDER BY 1",
+            start
+        );
+        let since = since(Duration::days(days as i64));
+
+        let rows: Vec<(DateTime<Utc>, i64, f64)> = db::with_retry(self.retry, || {
+            sqlx::query_as(&sql).bind(&since).fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(bucket, count, average)| FeedbackTrendPoint {
+                bucket,
+                count,
+                average,
+            })
+            .collect())
+    }
+
+    async fn category_counts(&self) -> Result<Vec<FeedbackCategoryCount>, ApiError> {
+        let rows: Vec<(String, i64, f64)> = db::with_retry(self.retry, || {
+            sqlx::query_as(
+                "SELECT COALESCE(category, $1), COUNT(*), ROUND(AVG(rating), 2)
+                FROM feedbacks
+                WHERE deleted_at IS NULL
+                GROUP BY 1
+                ORDER BY 2 DESC, 1",
+            )
+            .bind(UNCATEGORIZED)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(category, count, average_rating)| FeedbackCategoryCount {
+                category,
+                count,
+                average_rating,
+            })
+            .collect())
+    }
+
+    async fn low_rating_counts(
+        &self,
+        window_mins: i32,
+        baseline_days: i32,
+    ) -> Result<WindowCounts, ApiError> {
+        let window = Duration::minutes(window_mins as i64);
+        let window_start = since(window);
+        let baseline_start = since(window + Duration::days(baseline_days as i64));
+
+        let (current, baseline) = db::with_retry(self.retry, || {
+            sqlx::query_as(
+                "SELECT
+                    COUNT(CASE WHEN created_at > $2 THEN 1 END),
+                    COUNT(CASE WHEN created_at <= $2 THEN 1 END)
+                FROM feedbacks
+                WHERE rating = $1 AND created_at > $3",
+            )
+            .bind(MIN_RATING)
+            .bind(&window_start)
+            .bind(&baseline_start)
+            .fetch_one(&self.pool)
+        })
+        .await?;
+
+        Ok(WindowCounts { current, baseline })
+    }
+
+    async fn find_by_text(&self, text: &str) -> Result<Option<uuid::Uuid>, ApiError> {
+        let id = sqlx::query_scalar(
+            "SELECT id FROM feedbacks WHERE LOWER(text) = LOWER($1) AND deleted_at IS NULL",
+        )
+        .bind(text)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn most_similar(
+        &self,
+        text: &str,
+        threshold: f32,
+    ) -> Result<Option<uuid::Uuid>, ApiError> {
+        let live: Vec<(uuid::Uuid, String)> =
+            sqlx::query_as("SELECT id, text FROM feedbacks WHERE deleted_at IS NULL")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(live
+            .into_iter()
+            .map(|(id, other)| (id, similarity(&other, text)))
+            .filter(|&(_, score)| score > threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id))
+    }
+
+    async fn similar_to(
+        &self,
+        feedback: &FeedbackModel,
+        limit: usize,
+    ) -> Result<Vec<(FeedbackModel, f32)>, ApiError> {
+        let others = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, FeedbackModel>(
+                "SELECT * FROM feedbacks WHERE deleted_at IS NULL AND id <> $1",
+            )
+            .bind(feedback.id)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        let mut similar: Vec<(FeedbackModel, f32)> = others
+            .into_iter()
+            .map(|other| {
+                let score = similarity(&other.text, &feedback.text);
+                (other, score)
+            })
+            .filter(|&(_, score)| score > 0.0)
+            .collect();
+
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.id.cmp(&b.0.id)));
+        similar.truncate(limit);
+
+        Ok(similar)
+    }
+
+    fn stream_live(&self) -> BoxStream<'_, Result<FeedbackModel, ApiError>> {
+        sqlx::query_as::<_, FeedbackModel>(
+            "SELECT * FROM feedbacks WHERE deleted_at IS NULL ORDER BY created_at, id",
+        )
+        .fetch(&self.pool)
+        .map_err(ApiError::from)
+        .boxed()
+    }
+
+    async fn history(&self, id: uuid::Uuid) -> Result<Vec<AuditEntryModel>, ApiError> {
+        // Timestamps only have milliseconds, so entries written in the same
+        // one are put in the order they were inserted.
+        let entries = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, AuditEntryModel>(
+                "SELECT * FROM audit_log WHERE feedback_id = $1 ORDER BY created_at, rowid",
+            )
+            .bind(id)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        if entries.is_empty() {
+            sqlx::query("SELECT id FROM feedbacks WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+        }
+
+        Ok(entries)
+    }
+
+    async fn replies(&self, feedback_id: uuid::Uuid) -> Result<Vec<FeedbackReplyModel>, ApiError> {
+        ensure_feedback_exists(&self.pool, feedback_id).await?;
+
+        let replies = db::with_retry(self.retry, || {
+            sqlx::query_as::<_, FeedbackReplyModel>(
+                "SELECT * FROM feedback_replies WHERE feedback_id = $1 ORDER BY created_at, rowid",
+            )
+            .bind(feedback_id)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(replies)
+    }
+
+    async fn create(
+        &self,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+        let feedback = insert_feedback(&mut tx, body, default_rating, actor).await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn create_with_reply(
+        &self,
+        body: &CreateFeedbackSchema,
+        reply: &str,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<(FeedbackModel, FeedbackReplyModel), ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let feedback = insert_feedback(&mut tx, body, default_rating, actor).await?;
+        let reply = insert_reply(&mut tx, feedback.id, reply).await?;
+
+        tx.commit().await?;
+
+        Ok((feedback, reply))
+    }
+
+    async fn create_batch(
+        &self,
+        bodies: &[CreateFeedbackSchema],
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        // One insert per feedback rather than one for the batch, which keeps
+        // them in input order without matching `RETURNING` rows back up.
+        let mut feedbacks = Vec::with_capacity(bodies.len());
+        for body in bodies {
+            feedbacks.push(insert_feedback(&mut tx, body, default_rating, actor).await?);
+        }
+
+        tx.commit().await?;
+
+        Ok(feedbacks)
+    }
+
+    async fn begin_import(
+        &self,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Box<dyn FeedbackImport + '_>, ApiError> {
+        Ok(Box::new(SqliteImport {
+            tx: self.pool.begin().await?,
+            default_rating,
+            actor: actor.to_string(),
+        }))
+    }
+
+    async fn replace(
+        &self,
+        id: uuid::Uuid,
+        body: &CreateFeedbackSchema,
+        default_rating: i32,
+        actor: &str,
+    ) -> Result<Option<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(before) = lock_feedback(&mut tx, id).await? else {
+            return Ok(None);
+        };
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks
+            SET text = $1, rating = $2, category = $3, author_email = $4, metadata = $5,
+                updated_at = $6
+            WHERE id = $7 RETURNING *",
+        )
+        .bind(&body.text)
+        .bind(body.rating.unwrap_or(default_rating))
+        .bind(&body.category)
+        .bind(&body.author_email)
+        .bind(&body.metadata)
+        .bind(now())
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Update,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(feedback))
+    }
+
+    async fn update(
+        &self,
+        id: uuid::Uuid,
+        body: &UpdateFeedbackSchema,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        if body.is_empty() {
+            return self
+                .get(id)
+                .await?
+                .ok_or_else(|| ApiError::FeedbackNotFound(id));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        if body
+            .updated_at
+            .map_or(false, |updated_at| updated_at != before.updated_at)
+        {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} was modified by another request",
+                id
+            )));
+        }
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks
+            SET text = COALESCE($1, text),
+                rating = COALESCE($2, rating),
+                category = COALESCE($3, category),
+                updated_at = $4
+            WHERE id = $5
+            RETURNING *",
+        )
+        .bind(&body.text)
+        .bind(body.rating)
+        .bind(&body.category)
+        .bind(now())
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Update,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn set_rating(
+        &self,
+        ids: &[uuid::Uuid],
+        rating: i32,
+        actor: &str,
+    ) -> Result<Vec<FeedbackModel>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "UPDATE feedbacks SET rating = rating WHERE deleted_at IS NULL AND id IN",
+        );
+        push_ids(&mut query, ids);
+        query.push(" RETURNING *");
+        let before = query
+            .build_query_as::<FeedbackModel>()
+            .fetch_all(&mut tx)
+            .await?;
+
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE feedbacks SET rating = ");
+        query.push_bind(rating);
+        query.push(", updated_at = ").push_bind(now());
+        query.push(" WHERE deleted_at IS NULL AND id IN");
+        push_ids(&mut query, ids);
+        query.push(" RETURNING *");
+        let updated = query
+            .build_query_as::<FeedbackModel>()
+            .fetch_all(&mut tx)
+            .await?;
+
+        for feedback in &updated {
+            let before = before.iter().find(|before| before.id == feedback.id);
+            record(&mut tx, AuditAction::Update, actor, before, Some(feedback)).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    async fn vote(
+        &self,
+        id: uuid::Uuid,
+        upvotes: i32,
+        downvotes: i32,
+        actor: &str,
+    ) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or(ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks SET upvotes = upvotes + $1, downvotes = downvotes + $2
+            WHERE id = $3 RETURNING *",
+        )
+        .bind(upvotes)
+        .bind(downvotes)
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Vote,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn touch(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or(ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks SET updated_at = $1 WHERE id = $2 RETURNING *",
+        )
+        .bind(now())
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Touch,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn anonymize(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let placeholder = audit::redacted_text(id);
+
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_any_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks SET text = $1, author_email = NULL, metadata = NULL
+            WHERE id = $2 RETURNING *",
+        )
+        .bind(&placeholder)
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Anonymize,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        // Also scrubs the entry just recorded.
+        redact(&mut tx, id, &placeholder).await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn restore(&self, id: uuid::Uuid, actor: &str) -> Result<FeedbackModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_any_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        if before.deleted_at.is_none() {
+            return Err(ApiError::Conflict(format!(
+                "Feedback with ID: {} is not deleted",
+                id
+            )));
+        }
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks SET deleted_at = NULL WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Restore,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(feedback)
+    }
+
+    async fn delete(&self, id: uuid::Uuid, actor: &str) -> Result<(), ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let before = lock_feedback(&mut tx, id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(id))?;
+
+        let feedback = sqlx::query_as::<_, FeedbackModel>(
+            "UPDATE feedbacks SET deleted_at = $1 WHERE id = $2 RETURNING *",
+        )
+        .bind(now())
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+
+        record(
+            &mut tx,
+            AuditAction::Delete,
+            actor,
+            Some(&before),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_many(
+        &self,
+        ids: &[uuid::Uuid],
+        strict: bool,
+        actor: &str,
+    ) -> Result<Vec<uuid::Uuid>, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE feedbacks SET deleted_at = ");
+        query.push_bind(now());
+        query.push(" WHERE deleted_at IS NULL AND id IN");
+        push_ids(&mut query, ids);
+        query.push(" RETURNING *");
+        let deleted = query
+            .build_query_as::<FeedbackModel>()
+            .fetch_all(&mut tx)
+            .await?;
+
+        for feedback in &deleted {
+            let before = FeedbackModel {
+                deleted_at: None,
+                ..feedback.clone()
+            };
+            record(
+                &mut tx,
+                AuditAction::Delete,
+                actor,
+                Some(&before),
+                Some(feedback),
+            )
+            .await?;
+        }
+
+        let deleted: Vec<uuid::Uuid> = deleted.iter().map(|feedback| feedback.id).collect();
+
+        if strict {
+            let not_found = missing_ids(ids, &deleted);
+
+            if !not_found.is_empty() {
+                tx.rollback().await?;
+
+                return Err(not_found_error(&not_found));
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn delete_all(&self) -> Result<Vec<uuid::Uuid>, ApiError> {
+        // Replies go with their feedback through `ON DELETE CASCADE`.
+        let deleted = sqlx::query_scalar("DELETE FROM feedbacks RETURNING id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deleted)
+    }
+
+    async fn purge_expired(&self, retention_days: i32) -> Result<u64, ApiError> {
+        let cutoff = since(Duration::days(retention_days as i64));
+
+        let mut tx = self.pool.begin().await?;
+
+        let purged: Vec<uuid::Uuid> =
+            sqlx::query_scalar("DELETE FROM feedbacks WHERE created_at < $1 RETURNING id")
+                .bind(&cutoff)
+                .fetch_all(&mut tx)
+                .await?;
+
+        for &feedback_id in &purged {
+            redact(&mut tx, feedback_id, &audit::redacted_text(feedback_id)).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(purged.len() as u64)
+    }
+
+    async fn create_reply(
+        &self,
+        feedback_id: uuid::Uuid,
+        text: &str,
+        actor: &str,
+    ) -> Result<FeedbackReplyModel, ApiError> {
+        let mut tx = self.pool.begin().await?;
+
+        let feedback = lock_feedback(&mut tx, feedback_id)
+            .await?
+            .ok_or_else(|| ApiError::FeedbackNotFound(feedback_id))?;
+
+        let reply = insert_reply(&mut tx, feedback_id, text).await?;
+
+        record(
+            &mut tx,
+            AuditAction::Reply,
+            actor,
+            Some(&feedback),
+            Some(&feedback),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(reply)
+    }
+}
+
+struct SqliteImport {
+    tx: Transaction<'static, Sqlite>,
+    default_rating: i32,
+    actor: String,
+}
+
+#[async_trait]
+impl FeedbackImport for SqliteImport {
+    async fn insert(&mut self, bodies: &[CreateFeedbackSchema]) -> Result<Vec<usize>, ApiError> {
+        // Feedback are inserted one by one anyway, so each gets a savepoint and
+        // a collision only undoes that one.
+        let mut duplicates = Vec::new();
+        for (index, body) in bodies.iter().enumerate() {
+            let mut row = self.tx.begin().await?;
+            match insert_feedback(&mut row, body, self.default_rating, &self.actor).await {
+                Ok(_) => row.commit().await?,
+                Err(ApiError::Duplicate(_)) => {
+                    row.rollback().await?;
+                    duplicates.push(index);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(duplicates)
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), ApiError> {
+        self.tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::db::{Database, PoolSettings};
+
+    async fn repository() -> SqliteFeedbackRepository {
+        let database = PoolSettings::default()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        database.run_migrations().await.unwrap();
+
+        let Database::Sqlite { pool, .. } = database else {
+            panic!("sqlite::memory: did not connect to SQLite");
+        };
+
+        SqliteFeedbackRepository::new(pool, RetryPolicy::default())
+    }
+
+    fn body(value: serde_json::Value) -> CreateFeedbackSchema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn feedback_can_be_created_read_updated_and_deleted() {
+        let repo = repository().await;
+
+        let created = repo
+            .create(
+                &body(json!({ "text": "Great service", "metadata": { "source": "app" } })),
+                3,
+                "user-1",
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.rating, 3);
+        assert_eq!(
+            repo.get(created.id).await.unwrap().unwrap().text,
+            "Great service"
+        );
+
+        assert!(matches!(
+            repo.create(&body(json!({ "text": "GREAT SERVICE" })), 3, "user-1")
+                .await,
+            Err(ApiError::Duplicate(_))
+        ));
+
+        let update: UpdateFeedbackSchema =
+            serde_json::from_value(json!({ "rating": 5, "updatedAt": created.updated_at }))
+                .unwrap();
+        let updated = repo.update(created.id, &update, "user-1").await.unwrap();
+        assert_eq!(updated.rating, 5);
+        assert_eq!(updated.text, "Great service");
+
+        let voted = repo.vote(created.id, 2, 1, "user-2").await.unwrap();
+        assert_eq!((voted.upvotes, voted.downvotes), (2, 1));
+
+        let filter = ValidatedFilter::new(
+            FilterOptions::from_query("search=service&meta.source=app").unwrap(),
+            100,
+        )
+        .unwrap();
+        let found = repo.list(&filter).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, created.id);
+
+        repo.delete(created.id, "user-1").await.unwrap();
+        assert!(repo.get(created.id).await.unwrap().is_none());
+        // The text is free again once its feedback is deleted.
+        repo.create(&body(json!({ "text": "great service" })), 3, "user-1")
+            .await
+            .unwrap();
+
+        let actions: Vec<String> = repo
+            .history(created.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect();
+        assert_eq!(actions, ["create", "update", "vote", "delete"]);
+
+        assert_eq!(repo.delete_all().await.unwrap().len(), 2);
+        assert_eq!(repo.count(&FilterOptions::default()).await.unwrap(), 0);
+    }
+
+    #[actix_web::test]
+    async fn reports_are_computed_in_sqlite() {
+        let repo = repository().await;
+
+        let feedbacks = repo
+            .create_batch(
+                &[
+                    body(json!({ "text": "Checkout is slow", "rating": 1, "category": "checkout" })),
+                    body(json!({ "text": "Checkout is too slow", "rating": 2, "category": "checkout" })),
+                    body(json!({ "text": "Lovely colours", "rating": 5 })),
+                ],
+                3,
+                "user-1",
+            )
+            .await
+            .unwrap();
+        let texts: Vec<&str> = feedbacks
+            .iter()
+            .map(|feedback| feedback.text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            ["Checkout is slow", "Checkout is too slow", "Lovely colours"]
+        );
+
+        let categories = repo.category_counts().await.unwrap();
+        assert_eq!(categories[0].category, "checkout");
+        assert_eq!(categories[0].count, 2);
+        assert_eq!(categories[0].average_rating, 1.5);
+        assert_eq!(categories[1].category, UNCATEGORIZED);
+
+        for bucket in [TrendBucket::Day, TrendBucket::Week, TrendBucket::Month] {
+            let trend = repo.trend(bucket, 7).await.unwrap();
+            assert_eq!(trend.len(), 1);
+            assert_eq!(trend[0].count, 3);
+            assert!(trend[0].bucket <= feedbacks[0].created_at);
+        }
+
+        assert_eq!(
+            repo.low_rating_counts(60, 7).await.unwrap(),
+            WindowCounts {
+                current: 1,
+                baseline: 0
+            }
+        );
+        assert_eq!(repo.count_recent(1).await.unwrap(), 3);
+
+        let similar = repo.similar_to(&feedbacks[0], 1).await.unwrap();
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].0.id, feedbacks[1].id);
+        assert_eq!(
+            repo.most_similar("checkout is so slow", 0.3).await.unwrap(),
+            Some(feedbacks[0].id)
+        );
+
+        let ids = [feedbacks[2].id, feedbacks[0].id];
+        let rated = repo.set_rating(&ids, 4, "user-1").await.unwrap();
+        assert_eq!(rated.len(), 2);
+        let fetched: Vec<uuid::Uuid> = repo
+            .get_many(&ids)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|feedback| feedback.id)
+            .collect();
+        assert_eq!(fetched, ids);
+    }
+}
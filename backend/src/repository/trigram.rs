@@ -0,0 +1,28 @@
+use std::collections::HashSet;
+
+/// Trigram similarity as `pg_trgm` computes it: the share of trigrams of the
+/// space-padded lowercase words the two texts have in common.
+pub fn similarity(a: &str, b: &str) -> f32 {
+    fn trigrams(text: &str) -> HashSet<String> {
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .flat_map(|word| {
+                let padded: Vec<char> = format!("  {} ", word).chars().collect();
+                padded
+                    .windows(3)
+                    .map(|window| window.iter().collect())
+                    .collect::<Vec<String>>()
+            })
+            .collect()
+    }
+
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(&b).count() as f32 / union as f32
+}
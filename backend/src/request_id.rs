@@ -0,0 +1,103 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    time::Instant,
+};
+
+use actix_web::{
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, FromRequest, HttpMessage, HttpRequest,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequestId(pub Uuid);
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .copied()
+            .unwrap_or_else(|| RequestId(Uuid::new_v4()));
+
+        ready(Ok(request_id))
+    }
+}
+
+pub struct RequestTracing;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracing
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTracingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTracingMiddleware { service }))
+    }
+}
+
+pub struct RequestTracingMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = Uuid::new_v4();
+        req.extensions_mut().insert(RequestId(request_id));
+
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let start = Instant::now();
+
+        // Everything logged while the handler runs, slow query warnings
+        // included, carries the request it came from.
+        let span = tracing::info_span!("request", %request_id, %method, %path);
+        let fut = self.service.call(req).instrument(span);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+
+            tracing::info!(
+                %request_id,
+                %method,
+                %path,
+                status = res.status().as_u16(),
+                latency_ms = start.elapsed().as_millis() as u64,
+                "request completed"
+            );
+
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::rt;
+
+use crate::repository::FeedbackRepository;
+
+/// Runs `FeedbackRepository::purge_expired` every `interval`, starting right
+/// away. Failures are logged and retried on the next tick.
+pub fn spawn_cleanup_task(
+    repo: Arc<dyn FeedbackRepository>,
+    interval: Duration,
+    retention_days: i32,
+) {
+    rt::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+
+        loop {
+            ticks.tick().await;
+
+            match repo.purge_expired(retention_days).await {
+                Ok(0) => tracing::debug!(retention_days, "no feedback past retention"),
+                Ok(purged) => tracing::info!(purged, retention_days, "purged expired feedback"),
+                Err(e) => tracing::warn!(error = %e, "feedback retention cleanup failed"),
+            }
+        }
+    });
+}
@@ -1,9 +1,344 @@
-use serde::{Deserialize, Serialize};
+use crate::model::{FeedbackModel, FEEDBACK_FIELDS};
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use common::{Sentiment, ValidationErrors};
+use json_patch::{Patch, PatchOperation};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FilterOptions {
     pub page: Option<usize>,
     pub limit: Option<usize>,
+    pub after: Option<uuid::Uuid>,
+    pub search: Option<String>,
+    pub sort_by: Option<String>,
+    pub order: Option<String>,
+    /// Only feedback with exactly this rating; cannot be combined with
+    /// `min_rating` or `max_rating`.
+    pub rating: Option<i32>,
+    pub min_rating: Option<i32>,
+    pub max_rating: Option<i32>,
+    pub include_deleted: Option<bool>,
+    /// Matched case-insensitively.
+    #[serde(default, deserialize_with = "normalized_category")]
+    pub category: Option<String>,
+    pub sentiment: Option<Sentiment>,
+    /// RFC 3339 timestamp; only feedback created at or after it is returned.
+    pub created_after: Option<String>,
+    /// RFC 3339 timestamp; only feedback created at or before it is returned.
+    pub created_before: Option<String>,
+    /// Cut each `text` to this many characters and flag it as `truncated`.
+    pub preview_len: Option<usize>,
+    /// Built from `meta.<key>=<value>` parameters by [`FilterOptions::from_query`];
+    /// only feedback whose metadata contains every pair is returned.
+    #[serde(skip)]
+    #[param(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+fn parse_timestamp(name: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>, String> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|_| format!("{} must be an RFC 3339 timestamp, got {}", name, value))
+        })
+        .transpose()
+}
+
+/// Query parameters of the form `meta.<key>=<value>`.
+const METADATA_PARAM_PREFIX: &str = "meta.";
+
+impl FilterOptions {
+    /// Parses a query string, collecting `meta.*` parameters, which serde
+    /// cannot name up front, into a metadata object. Their values are matched
+    /// as strings.
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let mut options = web::Query::<FilterOptions>::from_query(query)
+            .map_err(|e| e.to_string())?
+            .into_inner();
+
+        let pairs = web::Query::<Vec<(String, String)>>::from_query(query)
+            .map_err(|e| e.to_string())?
+            .into_inner();
+        let metadata: serde_json::Map<String, serde_json::Value> = pairs
+            .into_iter()
+            .filter_map(|(name, value)| {
+                name.strip_prefix(METADATA_PARAM_PREFIX)
+                    .filter(|key| !key.is_empty())
+                    .map(|key| (key.to_string(), value.into()))
+            })
+            .collect();
+
+        if !metadata.is_empty() {
+            options.metadata = Some(metadata.into());
+        }
+
+        Ok(options)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rating.is_some() && (self.min_rating.is_some() || self.max_rating.is_some()) {
+            return Err("rating cannot be combined with min_rating or max_rating".to_string());
+        }
+
+        if let (Some(min_rating), Some(max_rating)) = (self.min_rating, self.max_rating) {
+            if min_rating > max_rating {
+                return Err("min_rating must not be greater than max_rating".to_string());
+            }
+        }
+
+        if self.preview_len == Some(0) {
+            return Err("preview_len must be at least 1".to_string());
+        }
+
+        if let (Some(after), Some(before)) = (self.created_after()?, self.created_before()?) {
+            if after > before {
+                return Err("created_after must not be later than created_before".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn created_after(&self) -> Result<Option<DateTime<Utc>>, String> {
+        parse_timestamp("created_after", self.created_after.as_deref())
+    }
+
+    pub fn created_before(&self) -> Result<Option<DateTime<Utc>>, String> {
+        parse_timestamp("created_before", self.created_before.as_deref())
+    }
+
+    pub fn search_term(&self) -> Option<&str> {
+        self.search
+            .as_deref()
+            .map(str::trim)
+            .filter(|search| !search.is_empty())
+    }
+
+    pub fn sort_column(&self) -> Result<Option<&'static str>, String> {
+        match self.sort_by.as_deref() {
+            None => Ok(None),
+            Some("id") => Ok(Some("id")),
+            Some("rating") => Ok(Some("rating")),
+            Some("created_at") => Ok(Some("created_at")),
+            Some("upvotes") => Ok(Some("upvotes")),
+            Some(other) => Err(format!(
+                "sort_by must be one of id, rating, created_at, upvotes, got {}",
+                other
+            )),
+        }
+    }
+
+    pub fn sort_order(&self) -> Result<&'static str, String> {
+        match self.order.as_deref() {
+            None | Some("asc") => Ok("ASC"),
+            Some("desc") => Ok("DESC"),
+            Some(other) => Err(format!("order must be one of asc, desc, got {}", other)),
+        }
+    }
+}
+
+pub const DEFAULT_RECENT_DAYS: i32 = 7;
+pub const MAX_RECENT_DAYS: i32 = 90;
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecentOptions {
+    /// How far back to look, in days. Defaults to 7 and is capped at 90.
+    pub days: Option<i32>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+impl RecentOptions {
+    pub fn days(&self) -> i32 {
+        self.days
+            .unwrap_or(DEFAULT_RECENT_DAYS)
+            .clamp(1, MAX_RECENT_DAYS)
+    }
+}
+
+pub const DEFAULT_ANOMALY_WINDOW_MINS: i32 = 60;
+pub const MAX_ANOMALY_WINDOW_MINS: i32 = 24 * 60;
+/// How far back the baseline for `/feedbacks/anomalies` looks.
+pub const ANOMALY_BASELINE_DAYS: i32 = 7;
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnomalyOptions {
+    /// Length of the window checked for a spike, in minutes. Defaults to 60 and
+    /// is capped at a day.
+    pub window_mins: Option<i32>,
+}
+
+impl AnomalyOptions {
+    pub fn window_mins(&self) -> i32 {
+        self.window_mins
+            .unwrap_or(DEFAULT_ANOMALY_WINDOW_MINS)
+            .clamp(1, MAX_ANOMALY_WINDOW_MINS)
+    }
+}
+
+pub const DEFAULT_TREND_DAYS: i32 = 30;
+pub const MAX_TREND_DAYS: i32 = 366;
+
+/// Period a trend groups feedback by, as understood by Postgres' `date_trunc`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrendBucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl TrendBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrendBucket::Day => "day",
+            TrendBucket::Week => "week",
+            TrendBucket::Month => "month",
+        }
+    }
+}
+
+impl FromStr for TrendBucket {
+    type Err = String;
+
+    fn from_str(bucket: &str) -> Result<Self, Self::Err> {
+        match bucket {
+            "day" => Ok(TrendBucket::Day),
+            "week" => Ok(TrendBucket::Week),
+            "month" => Ok(TrendBucket::Month),
+            other => Err(format!(
+                "bucket must be one of day, week, month; got {}",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrendOptions {
+    /// `day`, `week` or `month`. Defaults to `day`.
+    pub bucket: Option<String>,
+    /// How far back to look, in days. Defaults to 30 and is capped at 366.
+    pub days: Option<i32>,
+}
+
+impl TrendOptions {
+    pub fn bucket(&self) -> Result<TrendBucket, String> {
+        self.bucket
+            .as_deref()
+            .map_or(Ok(TrendBucket::Day), str::parse)
+    }
+
+    pub fn days(&self) -> i32 {
+        self.days
+            .unwrap_or(DEFAULT_TREND_DAYS)
+            .clamp(1, MAX_TREND_DAYS)
+    }
+}
+
+pub const DEFAULT_RANKED_LIMIT: usize = 10;
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RankedOptions {
+    /// How many feedback to return. Defaults to 10 and is capped at the
+    /// maximum page size.
+    pub limit: Option<usize>,
+}
+
+pub const DEFAULT_SIMILAR_LIMIT: usize = 5;
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SimilarOptions {
+    /// How many similar feedback to return. Defaults to 5.
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WipeOptions {
+    /// Must match the server's `WIPE_CONFIRM_TOKEN`.
+    pub confirm: Option<String>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreateOptions {
+    /// Reject the feedback with a 409 if it closely matches existing feedback.
+    /// Defaults to the server's `DEDUPE_BY_DEFAULT`.
+    pub dedupe: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsOptions {
+    /// Comma-separated field names to return, e.g. `id,rating`. Defaults to
+    /// every field.
+    pub fields: Option<String>,
+}
+
+impl FieldsOptions {
+    /// The requested fields, or `None` when the whole feedback was asked for.
+    pub fn selected(&self) -> Result<Option<Vec<&str>>, String> {
+        let Some(fields) = &self.fields else {
+            return Ok(None);
+        };
+
+        let selected: Vec<&str> = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .collect();
+
+        let unknown: Vec<&str> = selected
+            .iter()
+            .copied()
+            .filter(|field| !FEEDBACK_FIELDS.contains(field))
+            .collect();
+
+        if !unknown.is_empty() {
+            return Err(format!(
+                "unknown fields: {}; expected any of {}",
+                unknown.join(", "),
+                FEEDBACK_FIELDS.join(", ")
+            ));
+        }
+
+        if selected.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(selected))
+    }
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TimeZoneOptions {
+    /// IANA zone to show `created_at` and `updated_at` in, e.g.
+    /// `America/New_York`. Defaults to UTC.
+    pub tz: Option<String>,
+}
+
+impl TimeZoneOptions {
+    pub fn zone(&self) -> Result<Option<chrono_tz::Tz>, String> {
+        self.tz
+            .as_deref()
+            .map(|tz| {
+                tz.trim()
+                    .parse()
+                    .map_err(|_| format!("tz must be an IANA time zone, got {}", tz))
+            })
+            .transpose()
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -11,14 +346,359 @@ pub struct ParamOptions {
     pub id: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+pub const MIN_RATING: i32 = 1;
+pub const MAX_RATING: i32 = 5;
+pub const MAX_CATEGORY_LENGTH: usize = 50;
+
+pub const DEFAULT_MAX_TEXT_LENGTH: usize = 1000;
+pub const DEFAULT_RATING: i32 = 3;
+pub const DEFAULT_DEDUPE_THRESHOLD: f32 = 0.6;
+pub const DEFAULT_ANOMALY_MULTIPLIER: f64 = 3.0;
+pub const DEFAULT_MAX_BODY_SIZE: usize = 256 * 1024;
+
+fn validate_text(text: &str, max_length: usize) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("text must not be empty".to_string());
+    }
+
+    if text.chars().count() > max_length {
+        return Err(format!("text must be at most {} characters", max_length));
+    }
+
+    Ok(())
+}
+
+pub const MAX_EMAIL_LENGTH: usize = 254;
+
+/// A deliberately loose shape check: one `@`, something before it, and a
+/// dotted domain after it, with no whitespace.
+fn validate_email(email: &str) -> Result<(), String> {
+    let valid = email.chars().count() <= MAX_EMAIL_LENGTH
+        && !email.chars().any(char::is_whitespace)
+        && match email.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.split('.').all(|label| !label.is_empty())
+                    && domain.contains('.')
+            }
+            None => false,
+        };
+
+    if !valid {
+        return Err(format!(
+            "authorEmail must be a valid email address, got {}",
+            email
+        ));
+    }
+
+    Ok(())
+}
+
+/// Silently drops control characters such as NUL that break CSV and JSON
+/// consumers, keeping newlines, carriage returns and tabs, then trims the result.
+pub fn sanitize_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn sanitized<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    String::deserialize(deserializer).map(|text| sanitize_text(&text))
+}
+
+fn sanitized_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer).map(|text| text.as_deref().map(sanitize_text))
+}
+
+/// Categories are stored lowercase so "Bug", "bug" and "BUG" are the same
+/// category both on write and when filtering.
+pub fn normalize_category(category: &str) -> String {
+    category.trim().to_lowercase()
+}
+
+fn normalized_category<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer)
+        .map(|category| category.as_deref().map(normalize_category))
+}
+
+fn validate_rating(rating: i32) -> Result<(), String> {
+    if !(MIN_RATING..=MAX_RATING).contains(&rating) {
+        return Err(format!(
+            "rating must be between {} and {}",
+            MIN_RATING, MAX_RATING
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_category(category: &str) -> Result<(), String> {
+    if category.trim().is_empty() {
+        return Err("category must not be empty".to_string());
+    }
+
+    if category.chars().count() > MAX_CATEGORY_LENGTH {
+        return Err(format!(
+            "category must be at most {} characters",
+            MAX_CATEGORY_LENGTH
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct CreateFeedbackSchema {
+    #[serde(deserialize_with = "sanitized")]
     pub text: String,
-    pub rating: i32,
+    /// Falls back to the server's configured default rating when omitted.
+    pub rating: Option<i32>,
+    #[serde(default, deserialize_with = "normalized_category")]
+    pub category: Option<String>,
+    #[serde(rename = "authorEmail", default)]
+    pub author_email: Option<String>,
+    /// Any JSON object, stored as given.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl CreateFeedbackSchema {
+    pub fn validate(&self, max_text_length: usize) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        errors.check("text", validate_text(&self.text, max_text_length));
+
+        if let Some(rating) = self.rating {
+            errors.check("rating", validate_rating(rating));
+        }
+
+        if let Some(category) = &self.category {
+            errors.check("category", validate_category(category));
+        }
+
+        if let Some(author_email) = &self.author_email {
+            errors.check("authorEmail", validate_email(author_email));
+        }
+
+        if let Some(metadata) = &self.metadata {
+            if !metadata.is_object() {
+                errors.add("metadata", "metadata must be a JSON object");
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+/// One row of a CSV import. Columns other than `text`, `rating` and
+/// `category`, such as the rest of an export's, are ignored.
+#[derive(Deserialize, Debug)]
+pub struct ImportRow {
+    #[serde(deserialize_with = "sanitized")]
+    pub text: String,
+    /// An empty cell falls back to the default rating.
+    #[serde(default)]
+    pub rating: Option<i32>,
+    #[serde(default, deserialize_with = "normalized_category")]
+    pub category: Option<String>,
+}
+
+impl From<ImportRow> for CreateFeedbackSchema {
+    fn from(row: ImportRow) -> Self {
+        Self {
+            text: row.text,
+            rating: row.rating,
+            category: row.category,
+            author_email: None,
+            metadata: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct CreateFeedbackWithReplySchema {
+    pub feedback: CreateFeedbackSchema,
+    pub reply: CreateReplySchema,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl CreateFeedbackWithReplySchema {
+    pub fn validate(&self, max_text_length: usize) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        for (prefix, result) in [
+            ("feedback", self.feedback.validate(max_text_length)),
+            ("reply", self.reply.validate(max_text_length)),
+        ] {
+            if let Err(part_errors) = result {
+                for error in part_errors.errors {
+                    errors.add(format!("{}.{}", prefix, error.field), error.message);
+                }
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct BatchDeleteSchema {
+    pub ids: Vec<uuid::Uuid>,
+    /// Delete nothing unless every id exists. Defaults to best-effort.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct FetchByIdsSchema {
+    pub ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct UpdateFeedbackSchema {
+    #[serde(default, deserialize_with = "sanitized_option")]
     pub text: Option<String>,
     pub rating: Option<i32>,
+    #[serde(default, deserialize_with = "normalized_category")]
+    pub category: Option<String>,
+    /// The `updatedAt` the client last saw; the update is rejected with a 409
+    /// if the feedback has changed since.
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Fields a JSON Patch may write to; the rest are read-only.
+pub const PATCHABLE_FIELDS: &[&str] = &["text", "rating", "category"];
+
+impl UpdateFeedbackSchema {
+    /// The update that applying the RFC 6902 `patch` to `current` amounts to.
+    /// It carries `current`'s `updatedAt`, so persisting it fails with a 409
+    /// if the feedback changed after the patch was applied.
+    pub fn from_json_patch(current: &FeedbackModel, patch: &Patch) -> Result<Self, String> {
+        for operation in &patch.0 {
+            let written = match operation {
+                PatchOperation::Add(op) => vec![&op.path],
+                PatchOperation::Remove(op) => vec![&op.path],
+                PatchOperation::Replace(op) => vec![&op.path],
+                PatchOperation::Move(op) => vec![&op.from, &op.path],
+                PatchOperation::Copy(op) => vec![&op.path],
+                PatchOperation::Test(_) => vec![],
+            };
+
+            for path in written {
+                let field = path.split('/').nth(1).unwrap_or_default();
+                if !PATCHABLE_FIELDS.contains(&field) {
+                    return Err(format!(
+                        "{} is read-only; a patch can only change {}",
+                        if field.is_empty() { "/" } else { field },
+                        PATCHABLE_FIELDS.join(", ")
+                    ));
+                }
+            }
+        }
+
+        let original = serde_json::to_value(current).map_err(|e| e.to_string())?;
+        let mut patched = original.clone();
+        json_patch::patch(&mut patched, &patch.0).map_err(|e| format!("Invalid patch: {}", e))?;
+
+        if current.category.is_some() && patched["category"].is_null() {
+            return Err("category cannot be removed with PATCH; use PUT instead".to_string());
+        }
+
+        // Only what the patch actually changed, so a no-op patch does not bump
+        // `updatedAt`.
+        let changed: serde_json::Map<String, serde_json::Value> = PATCHABLE_FIELDS
+            .iter()
+            .filter(|field| patched[**field] != original[**field])
+            .map(|field| (field.to_string(), patched[*field].clone()))
+            .collect();
+
+        let mut update: Self = serde_json::from_value(changed.into())
+            .map_err(|e| format!("Patched feedback is invalid: {}", e))?;
+        update.updated_at = Some(current.updated_at);
+
+        Ok(update)
+    }
+
+    /// True when the body does not change any field.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.rating.is_none() && self.category.is_none()
+    }
+
+    pub fn validate(&self, max_text_length: usize) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if let Some(text) = &self.text {
+            errors.check("text", validate_text(text, max_text_length));
+        }
+
+        if let Some(rating) = self.rating {
+            errors.check("rating", validate_rating(rating));
+        }
+
+        if let Some(category) = &self.category {
+            errors.check("category", validate_category(category));
+        }
+
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct CreateReplySchema {
+    #[serde(deserialize_with = "sanitized")]
+    pub text: String,
+}
+
+impl CreateReplySchema {
+    pub fn validate(&self, max_text_length: usize) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        errors.check("text", validate_text(&self.text, max_text_length));
+
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct BulkRatingSchema {
+    pub ids: Vec<uuid::Uuid>,
+    pub rating: i32,
+}
+
+impl BulkRatingSchema {
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.ids.is_empty() {
+            errors.add("ids", "ids must not be empty");
+        }
+        errors.check("rating", validate_rating(self.rating));
+
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct VoteSchema {
+    /// Either `up` or `down`.
+    pub direction: String,
+}
+
+impl VoteSchema {
+    /// How much to add to the (upvotes, downvotes) counters.
+    pub fn increments(&self) -> Result<(i32, i32), String> {
+        match self.direction.as_str() {
+            "up" => Ok((1, 0)),
+            "down" => Ok((0, 1)),
+            other => Err(format!("direction must be one of up, down, got {}", other)),
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use sqlx::PgPool;
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+
+use crate::db::Database;
+
+const SAMPLE_FEEDBACK: &[(&str, i32, Option<&str>)] = &[
+    (
+        "Love the new dashboard, it is so much faster",
+        5,
+        Some("ui"),
+    ),
+    ("Search results are spot on now", 4, Some("search")),
+    (
+        "Checkout works but the flow feels long",
+        3,
+        Some("checkout"),
+    ),
+    ("The app crashes when I upload large photos", 1, Some("bug")),
+    (
+        "Dark mode would be a great addition",
+        4,
+        Some("feature-request"),
+    ),
+    (
+        "Notifications arrive several minutes late",
+        2,
+        Some("notifications"),
+    ),
+    ("Support answered my question within an hour", 5, None),
+    ("It does what it says, nothing more", 3, None),
+];
+
+/// Inserts the sample feedback when the table is empty and returns how many
+/// rows were created, so running it again is a no-op.
+pub async fn seed(database: &Database) -> Result<u64, sqlx::Error> {
+    match database {
+        Database::Postgres(pool) => seed_postgres(pool).await,
+        #[cfg(feature = "sqlite")]
+        Database::Sqlite { pool, .. } => seed_sqlite(pool).await,
+    }
+}
+
+async fn seed_postgres(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let existing = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM feedbacks"#)
+        .fetch_one(&mut tx)
+        .await?;
+
+    if existing > 0 {
+        return Ok(0);
+    }
+
+    let texts: Vec<String> = SAMPLE_FEEDBACK
+        .iter()
+        .map(|(text, _, _)| text.to_string())
+        .collect();
+    let ratings: Vec<i32> = SAMPLE_FEEDBACK
+        .iter()
+        .map(|(_, rating, _)| *rating)
+        .collect();
+    let categories: Vec<Option<String>> = SAMPLE_FEEDBACK
+        .iter()
+        .map(|(_, _, category)| category.map(str::to_string))
+        .collect();
+
+    let inserted = sqlx::query!(
+        "INSERT INTO feedbacks (text, rating, category)
+        SELECT * FROM UNNEST($1::text[], $2::int[], $3::varchar[])",
+        &texts,
+        &ratings,
+        &categories as &[Option<String>]
+    )
+    .execute(&mut tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(inserted)
+}
+
+#[cfg(feature = "sqlite")]
+async fn seed_sqlite(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM feedbacks")
+        .fetch_one(&mut tx)
+        .await?;
+
+    if existing > 0 {
+        return Ok(0);
+    }
+
+    let now = crate::db::sqlite_timestamp(chrono::Utc::now());
+    let mut inserted = 0;
+
+    for (text, rating, category) in SAMPLE_FEEDBACK {
+        inserted += sqlx::query(
+            "INSERT INTO feedbacks (id, text, rating, category, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)",
+        )
+        .bind(uuid::Uuid::new_v4())
+        .bind(text)
+        .bind(rating)
+        .bind(category)
+        .bind(&now)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+    }
+
+    tx.commit().await?;
+
+    Ok(inserted)
+}
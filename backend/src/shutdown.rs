@@ -0,0 +1,32 @@
+use tokio::signal;
+
+use crate::db::Database;
+
+/// Resolves with the name of the signal once SIGINT or SIGTERM is received.
+pub async fn wait_for_signal() -> &'static str {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("failed to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+    }
+}
+
+pub async fn close_pool(database: &Database) {
+    println!("🔌 Closing database connections");
+    database.close().await;
+    println!("👋 Shutdown complete");
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::OnceCell;
+
+/// Coalesces concurrent calls for the same key into one: the first caller
+/// runs the work and everyone who arrives while it is in flight gets a copy of
+/// its result.
+///
+/// Only successes are shared. If the work fails, the caller that ran it gets
+/// the error and the next one waiting runs it again, so errors don't need to
+/// be `Clone`.
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub async fn run<F, Fut, E>(&self, key: K, work: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let cell = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = cell.get_or_try_init(work).await.cloned();
+
+        // Whoever finishes first retires the entry, so calls from here on
+        // start a fresh query rather than reuse this result.
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .map_or(false, |current| Arc::ptr_eq(current, &cell))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+
+    /// Stops handing the result of the call in flight for `key` to new callers,
+    /// e.g. because what it reads just changed. Callers already waiting on it
+    /// still get it.
+    pub fn forget(&self, key: &K) {
+        self.in_flight.lock().unwrap().remove(key);
+    }
+
+    /// Like `forget`, for every key, e.g. after a write whose ids are unknown.
+    pub fn forget_all(&self) {
+        self.in_flight.lock().unwrap().clear();
+    }
+}
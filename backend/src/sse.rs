@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use actix_web::{get, http::header, rt, web, HttpResponse};
+use common::FeedbackEvent;
+use futures_util::stream;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+
+use crate::AppState;
+
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Server-sent events for newly created feedback, a lighter alternative to
+/// the WebSocket feed for clients that only listen.
+#[get("/feedbacks/events")]
+pub async fn feedback_events_handler(data: web::Data<AppState>) -> HttpResponse {
+    let mut events = data.events.subscribe();
+    let (tx, rx) = mpsc::channel::<Result<web::Bytes, actix_web::Error>>(16);
+
+    rt::spawn(async move {
+        // Comments keep idle connections from being closed by proxies.
+        let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+
+        loop {
+            let chunk = tokio::select! {
+                _ = keep_alive.tick() => web::Bytes::from_static(b": keep-alive\n\n"),
+                event = events.recv() => match event {
+                    Ok(FeedbackEvent::Created(feedback)) => {
+                        let Ok(json) = serde_json::to_string(&feedback) else {
+                            continue;
+                        };
+
+                        web::Bytes::from(format!("event: created\ndata: {}\n\n", json))
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            if tx.send(Ok(chunk)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Compressing would buffer events until the encoder flushes.
+        .insert_header((header::CONTENT_ENCODING, "identity"))
+        .streaming(body)
+}
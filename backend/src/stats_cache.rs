@@ -0,0 +1,67 @@
+use std::{
+    collections::BTreeMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+/// Aggregates behind `GET /feedbacks/stats`.
+#[derive(Debug, Clone)]
+pub struct FeedbackStats {
+    pub average: f64,
+    pub count: i64,
+    pub distribution: BTreeMap<u8, i64>,
+}
+
+struct Entry {
+    generation: u64,
+    stored: Option<(Instant, FeedbackStats)>,
+}
+
+/// Keeps the last computed stats for `ttl`, dropping them as soon as feedback
+/// changes.
+///
+/// Stats computed while a write lands would be stale the moment they are
+/// stored, so [`StatsCache::insert`] takes the generation read before the
+/// query and ignores the result if an invalidation happened since.
+pub struct StatsCache {
+    ttl: Duration,
+    entry: RwLock<Entry>,
+}
+
+impl StatsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: RwLock::new(Entry {
+                generation: 0,
+                stored: None,
+            }),
+        }
+    }
+
+    /// The cached stats if they are still fresh, otherwise the generation to
+    /// hand back to `insert` once they have been recomputed.
+    pub fn get(&self) -> Result<FeedbackStats, u64> {
+        let entry = self.entry.read().unwrap_or_else(|e| e.into_inner());
+
+        match &entry.stored {
+            Some((stored_at, stats)) if stored_at.elapsed() < self.ttl => Ok(stats.clone()),
+            _ => Err(entry.generation),
+        }
+    }
+
+    pub fn insert(&self, generation: u64, stats: FeedbackStats) {
+        let mut entry = self.entry.write().unwrap_or_else(|e| e.into_inner());
+
+        if entry.generation == generation {
+            entry.stored = Some((Instant::now(), stats));
+        }
+    }
+
+    pub fn invalidate(&self) {
+        let mut entry = self.entry.write().unwrap_or_else(|e| e.into_inner());
+
+        entry.generation = entry.generation.wrapping_add(1);
+        entry.stored = None;
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    time::Duration,
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web, Error,
+};
+
+use crate::{error::ApiError, AppState};
+
+pub const DEFAULT_ROUTE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs `operation`, giving up with a 504 once `budget` has passed. Dropping
+/// it cancels the query in flight and rolls back any transaction it held.
+pub async fn with_timeout<F: Future>(
+    budget: Duration,
+    operation: F,
+) -> Result<F::Output, ApiError> {
+    tokio::time::timeout(budget, operation)
+        .await
+        .map_err(|_| ApiError::Timeout(budget))
+}
+
+/// How long each route may take to produce its response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteTimeouts {
+    default: Duration,
+    /// Keyed by route pattern below the API scope, e.g. `/feedbacks/{id}`.
+    routes: HashMap<String, Duration>,
+}
+
+impl RouteTimeouts {
+    /// Reads the default budget from `ROUTE_TIMEOUT_MS` and per-route ones
+    /// from `ROUTE_TIMEOUTS_MS`, a comma-separated list of `pattern=ms` such
+    /// as `/feedbacks/stats=2000,/feedbacks/export.csv=120000`. Malformed
+    /// entries are ignored.
+    pub fn from_env() -> Self {
+        let default = std::env::var("ROUTE_TIMEOUT_MS")
+            .ok()
+            .and_then(|ms| ms.trim().parse().ok())
+            .filter(|&ms: &u64| ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_ROUTE_TIMEOUT);
+
+        let routes = std::env::var("ROUTE_TIMEOUTS_MS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|entry| {
+                let (pattern, ms) = entry.split_once('=')?;
+                let ms: u64 = ms.trim().parse().ok().filter(|&ms| ms > 0)?;
+
+                Some((pattern.trim().to_string(), Duration::from_millis(ms)))
+            })
+            .collect();
+
+        Self { default, routes }
+    }
+
+    /// The budget for `pattern`, the route relative to the API scope.
+    pub fn budget(&self, pattern: &str) -> Duration {
+        self.routes.get(pattern).copied().unwrap_or(self.default)
+    }
+}
+
+/// Answers 504 for requests whose handler outlives its route's budget. Only
+/// producing the response is timed; a streamed body can take longer.
+///
+/// Attached to each route with `wrap = "RouteTimeout"` rather than to the
+/// whole app: the 504 is built from a clone of the request, and routing
+/// needs the request to itself until it has matched.
+pub struct RouteTimeout;
+
+impl<S, B> Transform<S, ServiceRequest> for RouteTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RouteTimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RouteTimeoutMiddleware { service }))
+    }
+}
+
+pub struct RouteTimeoutMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RouteTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let budget = match req.app_data::<web::Data<AppState>>() {
+            Some(data) => {
+                let pattern = req.match_pattern().unwrap_or_default();
+                let route = pattern.strip_prefix(&data.api_scope).unwrap_or(&pattern);
+                data.route_timeouts.budget(route)
+            }
+            None => DEFAULT_ROUTE_TIMEOUT,
+        };
+        let http_req = req.request().clone();
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            match with_timeout(budget, fut).await {
+                Ok(res) => res.map(ServiceResponse::map_into_left_body),
+                // Built as a response rather than returned as an error so the
+                // middleware outside, CORS included, still sees it.
+                Err(e) => Ok(ServiceResponse::from_err(e, http_req).map_into_right_body()),
+            }
+        })
+    }
+}
@@ -0,0 +1,57 @@
+use std::future::{ready, Ready};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use chrono::{DateTime, SecondsFormat};
+use chrono_tz::Tz;
+
+use crate::{error::ApiError, schema::TimeZoneOptions};
+
+/// Fields rewritten into the display zone.
+const ZONED_FIELDS: [&str; 2] = ["createdAt", "updatedAt"];
+
+/// Zone the request's `?tz=` asked timestamps to be shown in. Without one they
+/// stay in UTC.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayZone(Option<Tz>);
+
+impl DisplayZone {
+    /// Rewrites the timestamps of the serialized feedback `value` into this
+    /// zone, leaving anything that is not an RFC 3339 string alone.
+    pub fn apply(&self, value: &mut serde_json::Value) {
+        let (Some(zone), Some(object)) = (self.0, value.as_object_mut()) else {
+            return;
+        };
+
+        for field in ZONED_FIELDS {
+            let Some(timestamp) = object
+                .get(field)
+                .and_then(|timestamp| timestamp.as_str())
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            else {
+                continue;
+            };
+
+            object.insert(
+                field.to_string(),
+                timestamp
+                    .with_timezone(&zone)
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                    .into(),
+            );
+        }
+    }
+}
+
+impl FromRequest for DisplayZone {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let zone = web::Query::<TimeZoneOptions>::from_query(req.query_string())
+            .map_err(|e| ApiError::Validation(e.to_string()))
+            .and_then(|opts| opts.zone().map_err(ApiError::Validation))
+            .map(DisplayZone);
+
+        ready(zone)
+    }
+}
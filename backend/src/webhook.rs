@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use actix_web::rt;
+use common::Feedback;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts newly created feedback to an external URL, e.g. a Slack or Zapier
+/// hook.
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl Webhook {
+    /// The webhook configured by `FEEDBACK_WEBHOOK_URL`, if any.
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("FEEDBACK_WEBHOOK_URL")
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())?;
+
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .ok()?;
+
+        Some(Self { client, url })
+    }
+
+    /// Sends `feedback` in the background; the caller never waits for or sees
+    /// the outcome, failures are only logged.
+    pub fn dispatch(&self, feedback: Feedback) {
+        let request = self.client.post(&self.url).json(&feedback);
+        let feedback_id = feedback.id;
+
+        rt::spawn(async move {
+            match request.send().await.and_then(|res| res.error_for_status()) {
+                Ok(_) => tracing::debug!(%feedback_id, "feedback webhook delivered"),
+                Err(e) => tracing::warn!(%feedback_id, error = %e, "feedback webhook failed"),
+            }
+        });
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
@@ -0,0 +1,52 @@
+use actix_web::{get, rt, web, Error, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::AppState;
+
+#[get("/feedbacks/ws")]
+pub async fn feedback_ws_handler(
+    req: HttpRequest,
+    body: web::Payload,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, Error> {
+    let (response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let mut events = data.events.subscribe();
+
+    let mut pong_session = session.clone();
+    rt::spawn(async move {
+        while let Some(Ok(msg)) = msg_stream.recv().await {
+            match msg {
+                Message::Ping(bytes) => {
+                    if pong_session.pong(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    rt::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let Ok(message) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+
+                    if session.text(message).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::{ErrorResponse, FeedbackListResponse, FeedbackResponse};
+
+#[derive(Debug)]
+pub enum ClientError {
+    Request(reqwest::Error),
+    Api(StatusCode, ErrorResponse),
+    Status(StatusCode),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Request(e) => write!(f, "Failed to make request: {}", e),
+            ClientError::Api(_, error_response) => write!(f, "{}", error_response.message),
+            ClientError::Status(status) => write!(f, "API error: {}", status),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Request(e)
+    }
+}
+
+#[derive(Serialize)]
+struct CreateFeedbackBody<'a> {
+    text: &'a str,
+    rating: u8,
+}
+
+#[derive(Serialize)]
+struct UpdateFeedbackBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedbackClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl FeedbackClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub async fn list(
+        &self,
+        page: usize,
+        limit: usize,
+    ) -> Result<FeedbackListResponse, ClientError> {
+        let request = self
+            .http
+            .get(self.url("/api/v1/feedbacks"))
+            .query(&[("page", page), ("limit", limit)]);
+
+        parse(self.send(request).await?).await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<FeedbackResponse, ClientError> {
+        let request = self
+            .http
+            .get(self.url(&format!("/api/v1/feedbacks/{}", id)));
+
+        parse(self.send(request).await?).await
+    }
+
+    pub async fn create(&self, text: &str, rating: u8) -> Result<FeedbackResponse, ClientError> {
+        let request = self
+            .http
+            .post(self.url("/api/v1/feedbacks/"))
+            .json(&CreateFeedbackBody { text, rating });
+
+        parse(self.send(request).await?).await
+    }
+
+    pub async fn update(
+        &self,
+        id: Uuid,
+        text: Option<&str>,
+        rating: Option<u8>,
+    ) -> Result<FeedbackResponse, ClientError> {
+        let request = self
+            .http
+            .patch(self.url(&format!("/api/v1/feedbacks/{}", id)))
+            .json(&UpdateFeedbackBody { text, rating });
+
+        parse(self.send(request).await?).await
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), ClientError> {
+        let request = self
+            .http
+            .delete(self.url(&format!("/api/v1/feedbacks/{}", id)));
+
+        self.send(request).await?;
+
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ClientError> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            return match response.json::<ErrorResponse>().await {
+                Ok(error_response) => Err(ClientError::Api(status, error_response)),
+                Err(_) => Err(ClientError::Status(status)),
+            };
+        }
+
+        Ok(response)
+    }
+}
+
+async fn parse<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    Ok(response.json::<T>().await?)
+}
@@ -1,33 +1,581 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, ops::RangeInclusive};
 use uuid::Uuid;
 
+#[cfg(feature = "client")]
+pub mod client;
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Feedback {
     pub id: Uuid,
     pub text: String,
     pub rating: u8,
+    pub category: Option<String>,
+    #[serde(
+        rename = "authorEmail",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub author_email: Option<String>,
+    /// Free-form context attached by the integration that sent the feedback,
+    /// e.g. browser or app version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub metadata: Option<serde_json::Value>,
+    pub upvotes: i32,
+    pub downvotes: i32,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Feedback {
+    pub fn builder() -> FeedbackBuilder {
+        FeedbackBuilder::default()
+    }
+
+    pub fn sentiment(&self) -> Sentiment {
+        Sentiment::from_rating(self.rating)
+    }
+}
+
+/// Builds a [`Feedback`] with a fresh id and timestamps, so callers only set
+/// the fields they care about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedbackBuilder {
+    text: String,
+    rating: u8,
+    category: Option<String>,
+    author_email: Option<String>,
+    metadata: Option<serde_json::Value>,
+}
+
+impl Default for FeedbackBuilder {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            rating: 5,
+            category: None,
+            author_email: None,
+            metadata: None,
+        }
+    }
+}
+
+impl FeedbackBuilder {
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn rating(mut self, rating: u8) -> Self {
+        self.rating = rating;
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn author_email(mut self, author_email: impl Into<String>) -> Self {
+        self.author_email = Some(author_email.into());
+        self
+    }
+
+    pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    pub fn build(self) -> Feedback {
+        let now = Utc::now();
+
+        Feedback {
+            id: Uuid::new_v4(),
+            text: self.text,
+            rating: self.rating,
+            category: self.category,
+            author_email: self.author_email,
+            metadata: self.metadata,
+            upvotes: 0,
+            downvotes: 0,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        }
+    }
+}
+
+/// Coarse classification of a rating, shared so every client buckets the same way.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Sentiment {
+    Negative,
+    Neutral,
+    Positive,
+}
+
+impl Sentiment {
+    pub fn from_rating(rating: u8) -> Self {
+        match rating {
+            0..=2 => Sentiment::Negative,
+            3 => Sentiment::Neutral,
+            _ => Sentiment::Positive,
+        }
+    }
+
+    /// The ratings that fall into this sentiment.
+    pub fn ratings(self) -> RangeInclusive<u8> {
+        match self {
+            Sentiment::Negative => 1..=2,
+            Sentiment::Neutral => 3..=3,
+            Sentiment::Positive => 4..=5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", content = "data")]
+pub enum FeedbackEvent {
+    Created(Feedback),
+    Updated(Feedback),
+    Deleted { id: Uuid },
+    Replied(FeedbackReply),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FeedbackData {
     pub feedback: Feedback,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FeedbackResponse {
-    pub status: String,
+    pub status: ResponseStatus,
     pub data: FeedbackData,
 }
 
+/// One page of a list paginated by offset: `items` are the `page`th run of
+/// at most `limit` out of `total`. Pages are numbered from 1.
+///
+/// Serializes with `total_pages` and `has_next` alongside the fields, for
+/// clients that do not share this type.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::ToSchema),
+    aliases(
+        FeedbackPage = Page<Feedback>,
+        SimilarFeedbackPage = Page<SimilarFeedback>,
+        FeedbackReplyPage = Page<FeedbackReply>,
+        AuditEntryPage = Page<AuditEntry>
+    )
+)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: usize,
+    pub limit: usize,
+    /// Id to pass as `after` for the next page when paginating by cursor.
+    #[serde(default)]
+    pub next_cursor: Option<Uuid>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, page: usize, limit: usize) -> Self {
+        Self {
+            items,
+            total,
+            page,
+            limit,
+            next_cursor: None,
+        }
+    }
+
+    /// All of `items` as the only page, for lists that are not paginated.
+    pub fn single(items: Vec<T>) -> Self {
+        let total = items.len();
+
+        Self::new(items, total as i64, 1, total)
+    }
+
+    pub fn with_next_cursor(mut self, next_cursor: Option<Uuid>) -> Self {
+        self.next_cursor = next_cursor;
+        self
+    }
+
+    /// How many pages of `limit` it takes to hold `total`; 0 when the list is
+    /// empty.
+    pub fn total_pages(&self) -> usize {
+        match self.limit {
+            0 => 0,
+            limit => (self.total.max(0) as usize).div_ceil(limit),
+        }
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages()
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            limit: self.limit,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Page<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Body<'a, T> {
+            items: &'a [T],
+            total: i64,
+            page: usize,
+            limit: usize,
+            total_pages: usize,
+            has_next: bool,
+            next_cursor: Option<Uuid>,
+        }
+
+        Body {
+            items: &self.items,
+            total: self.total,
+            page: self.page,
+            limit: self.limit,
+            total_pages: self.total_pages(),
+            has_next: self.has_next(),
+            next_cursor: self.next_cursor,
+        }
+        .serialize(serializer)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FeedbackListResponse {
-    pub status: String,
-    pub results: i32,
-    pub feedbacks: Vec<Feedback>,
+    pub status: ResponseStatus,
+    #[cfg_attr(feature = "openapi", schema(value_type = FeedbackPage))]
+    pub data: Page<Feedback>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackStatsResponse {
+    pub status: ResponseStatus,
+    pub average: f64,
+    pub count: i64,
+    pub distribution: BTreeMap<u8, i64>,
+}
+
+/// Feedback created in one trend bucket, keyed by the bucket's start.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackTrendPoint {
+    pub bucket: DateTime<Utc>,
+    pub count: i64,
+    pub average: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackTrendResponse {
+    pub status: ResponseStatus,
+    pub bucket: String,
+    pub trend: Vec<FeedbackTrendPoint>,
+}
+
+/// Feedback filed under one category. Feedback without a category is counted
+/// under `"uncategorized"`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackCategoryCount {
+    pub category: String,
+    pub count: i64,
+    pub average_rating: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackCategoryResponse {
+    pub status: ResponseStatus,
+    pub categories: Vec<FeedbackCategoryCount>,
 }
 
+/// Whether the lowest rating is arriving much faster than usual.
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackAnomalyResponse {
+    pub status: ResponseStatus,
+    pub window_mins: i32,
+    /// Lowest-rated feedback created in the window.
+    pub count: i64,
+    /// Average lowest-rated feedback per window over the baseline period.
+    pub baseline: f64,
+    pub threshold: f64,
+    pub anomalous: bool,
+}
+
+/// A feedback with its trigram similarity to the one it was compared with,
+/// from 0 (nothing in common) to 1 (identical).
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SimilarFeedback {
+    #[serde(flatten)]
+    pub feedback: Feedback,
+    pub similarity: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackSimilarResponse {
+    pub status: ResponseStatus,
+    #[cfg_attr(feature = "openapi", schema(value_type = SimilarFeedbackPage))]
+    pub data: Page<SimilarFeedback>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackCountResponse {
+    pub status: ResponseStatus,
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackBatchDeleteResponse {
+    pub status: ResponseStatus,
+    pub deleted: u64,
+    pub not_found: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackBulkUpdateResponse {
+    pub status: ResponseStatus,
+    pub updated: u64,
+}
+
+/// Why a CSV row was not imported; `row` 1 is the first after the header.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImportRowError {
+    pub row: u64,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackImportResponse {
+    pub status: ResponseStatus,
+    pub imported: u64,
+    pub skipped: u64,
+    /// The first errors found; `skipped` still counts every skipped row.
+    pub errors: Vec<ImportRowError>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackWipeResponse {
+    pub status: ResponseStatus,
+    pub deleted: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackReply {
+    pub id: Uuid,
+    #[serde(rename = "feedbackId")]
+    pub feedback_id: Uuid,
+    pub text: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackReplyData {
+    pub reply: FeedbackReply,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackReplyResponse {
+    pub status: ResponseStatus,
+    pub data: FeedbackReplyData,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackWithReplyData {
+    pub feedback: Feedback,
+    pub reply: FeedbackReply,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackWithReplyResponse {
+    pub status: ResponseStatus,
+    pub data: FeedbackWithReplyData,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackReplyListResponse {
+    pub status: ResponseStatus,
+    #[cfg_attr(feature = "openapi", schema(value_type = FeedbackReplyPage))]
+    pub data: Page<FeedbackReply>,
+}
+
+/// One change to a feedback; `before` is absent for a create.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuditEntry {
+    pub id: Uuid,
+    #[serde(rename = "feedbackId")]
+    pub feedback_id: Uuid,
+    pub action: String,
+    pub actor: String,
+    pub before: Option<Feedback>,
+    pub after: Option<Feedback>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FeedbackHistoryResponse {
+    pub status: ResponseStatus,
+    #[cfg_attr(feature = "openapi", schema(value_type = AuditEntryPage))]
+    pub data: Page<AuditEntry>,
+}
+
+/// Outcome every response reports in its `status` field: `success` when the
+/// request worked, `fail` when the client was at fault and `error` when the
+/// server was.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseStatus {
+    Success,
+    Fail,
+    Error,
+}
+
+/// The envelope responses are wrapped in: `{"status": "success", "data": ...}`
+/// when the request worked, `{"status": "fail" | "error", "message": ...}`
+/// when the client or the server was at fault.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum ApiResponse<T> {
+    Success { data: T },
+    Fail { message: String },
+    Error { message: String },
+}
+
+impl<T> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        ApiResponse::Success { data }
+    }
+
+    pub fn fail(message: impl Into<String>) -> Self {
+        ApiResponse::Fail {
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        ApiResponse::Error {
+            message: message.into(),
+        }
+    }
+
+    /// The data on success, or the message of a failed or errored response.
+    pub fn into_result(self) -> Result<T, String> {
+        match self {
+            ApiResponse::Success { data } => Ok(data),
+            ApiResponse::Fail { message } | ApiResponse::Error { message } => Err(message),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ErrorResponse {
-    pub status: String,
+    pub status: ResponseStatus,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found in a request body, so a form can flag all of its
+/// fields at once instead of one per submit.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Records the error from `result`, if any, against `field`.
+    pub fn check(&mut self, field: &str, result: Result<(), String>) {
+        if let Err(message) = result {
+            self.add(field, message);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self
+            .errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect();
+
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ValidationErrorResponse {
+    pub status: ResponseStatus,
     pub message: String,
+    pub errors: Vec<FieldError>,
 }
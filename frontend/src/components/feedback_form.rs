@@ -15,7 +15,7 @@ pub fn FeedbackForm() -> Html {
     let (store, dispatch) = use_store::<Store>();
     let loading = &store.loading;
     let text = use_state(String::new);
-    let rating = use_state(|| 10_u8);
+    let rating = use_state(|| 5_u8);
     let min = use_state(|| 10);
     let message = use_state(|| Option::<String>::None);
 
@@ -69,7 +69,7 @@ pub fn FeedbackForm() -> Html {
                 let text_input = text_input_ref.cast::<HtmlInputElement>().unwrap();
                 text_input.set_value("");
                 text.set(String::new());
-                rating.set(10);
+                rating.set(5);
 
                 let response = api_create_feedback(feedback_data.to_string().as_str()).await;
 
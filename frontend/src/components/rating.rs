@@ -24,7 +24,7 @@ pub fn Rating(props: &Props) -> Html {
 
     html! {
         <ul class="list-none flex items-center justify-around my-7">
-            { for (1..=10).map(|i| {
+            { for (1..=5).map(|i| {
                 let label = i.to_string();
                 let id = format!("num{}", i);
 